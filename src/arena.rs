@@ -0,0 +1,39 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+// --- Arena ---
+// Wall cells that shape the playfield into a cave, belt or any other layout.
+// Level files are plain text: '#' is a wall, anything else is open space,
+// one line per row starting from the top-left of the playfield.
+#[derive(Debug, Clone, Default)]
+pub struct Arena {
+    walls: HashSet<(u16, u16)>,
+}
+
+impl Arena {
+    pub fn parse(text: &str) -> Self {
+        let walls = text.lines().enumerate()
+            .flat_map(|(y, line)| {
+                line.chars().enumerate()
+                    .filter(|&(_, ch)| ch == '#')
+                    .map(move |(x, _)| (x as u16, y as u16))
+            })
+            .collect();
+        Arena { walls }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        fs::read_to_string(path)
+            .map(|text| Arena::parse(&text))
+            .map_err(|e| format!("could not read {}: {}", path.display(), e))
+    }
+
+    pub fn is_wall(&self, x: u16, y: u16) -> bool {
+        self.walls.contains(&(x, y))
+    }
+
+    pub fn walls(&self) -> impl Iterator<Item = &(u16, u16)> {
+        self.walls.iter()
+    }
+}
@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+// --- Attract Loop ---
+// Kiosk mode: left idle on the title screen, the game shows off the high-score
+// table and a demo run in turn until someone presses a key
+const SCREEN_TIME: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttractScreen {
+    Menu,
+    HighScores,
+    Demo,
+}
+
+pub struct Attract {
+    pub screen: AttractScreen,
+    // Time spent on the current screen
+    shown_for: Duration,
+    idle_timeout: Duration,
+}
+
+impl Attract {
+    pub fn new(idle_timeout: Duration) -> Self {
+        Attract { screen: AttractScreen::Menu, shown_for: Duration::ZERO, idle_timeout }
+    }
+
+    pub fn advance(&mut self, dt: Duration) {
+        self.shown_for += dt;
+        let next = match self.screen {
+            AttractScreen::Menu if self.shown_for >= self.idle_timeout => AttractScreen::HighScores,
+            AttractScreen::HighScores if self.shown_for >= SCREEN_TIME => AttractScreen::Demo,
+            AttractScreen::Demo if self.shown_for >= SCREEN_TIME => AttractScreen::HighScores,
+            _ => return,
+        };
+        self.screen = next;
+        self.shown_for = Duration::ZERO;
+    }
+
+    // Any key brings the menu back; returns whether the menu was already up,
+    // in which case the key starts the game
+    pub fn key_pressed(&mut self) -> bool {
+        let on_menu = self.screen == AttractScreen::Menu;
+        self.screen = AttractScreen::Menu;
+        self.shown_for = Duration::ZERO;
+        on_menu
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn idle_menu_cycles_to_the_demo() {
+        let mut attract = Attract::new(Duration::from_secs(30));
+        attract.advance(Duration::from_secs(29));
+        assert_eq!(attract.screen, AttractScreen::Menu);
+        attract.advance(Duration::from_secs(1));
+        assert_eq!(attract.screen, AttractScreen::HighScores);
+        attract.advance(SCREEN_TIME);
+        assert_eq!(attract.screen, AttractScreen::Demo);
+        attract.advance(SCREEN_TIME);
+        assert_eq!(attract.screen, AttractScreen::HighScores);
+    }
+
+    #[test]
+    fn key_returns_to_the_menu() {
+        let mut attract = Attract::new(Duration::ZERO);
+        attract.advance(Duration::ZERO);
+        attract.advance(SCREEN_TIME);
+        assert_eq!(attract.screen, AttractScreen::Demo);
+        // The key only brings the menu back; it doesn't start a game
+        assert!(!attract.key_pressed());
+        assert_eq!(attract.screen, AttractScreen::Menu);
+        assert!(attract.key_pressed());
+    }
+}
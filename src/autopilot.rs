@@ -0,0 +1,169 @@
+use crate::arena::Arena;
+use crate::geometry::in_bounds;
+use crate::{fuel_state, glyph_cells, Asteroid, FuelState, InputEvent, Rect, ResourceNode, Ship, SHIP_WIDTH};
+
+// --- Autopilot ---
+// Flies the ship when no key is pressed: seeks out and mines resources, and
+// flees to open space when things get dangerous.
+
+// Asteroids within this many cells count towards being surrounded
+const SURROUND_RADIUS: u16 = 2;
+const SURROUND_COUNT: usize = 3;
+// How far away a resource can be and still count as "nearby" when fuel is low
+const SAFE_MINING_RANGE: u16 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutopilotMode {
+    Seek,
+    Flee,
+}
+
+const MOVES: [InputEvent; 5] = [
+    InputEvent::None,
+    InputEvent::Up,
+    InputEvent::Down,
+    InputEvent::Left,
+    InputEvent::Right,
+];
+
+fn distance(ax: u16, ay: u16, bx: u16, by: u16) -> u16 {
+    ax.abs_diff(bx).max(ay.abs_diff(by))
+}
+
+fn target_cell(ship: &Ship, input: InputEvent, bounds: &Rect, arena: &Arena) -> Option<(u16, u16)> {
+    let (dx, dy) = input.delta();
+    let (x, y) = (ship.x as i32 + dx, ship.y as i32 + dy);
+    let open = in_bounds(x, y, bounds) && !glyph_cells(x as u16, y as u16).any(|(x, y)| arena.is_wall(x, y));
+    open.then_some((x as u16, y as u16))
+}
+
+// Sum of inverse-square proximity to every asteroid; lower is safer
+pub fn danger_at(x: u16, y: u16, asteroids: &[Asteroid]) -> f32 {
+    asteroids.iter()
+        .map(|a| {
+            let d = distance(x, y, a.x, a.y) as f32;
+            1.0 / (d * d).max(0.01)
+        })
+        .sum()
+}
+
+// A node is safe to mine if no asteroid sits right next to it
+fn safe_node(node: &ResourceNode, asteroids: &[Asteroid]) -> bool {
+    asteroids.iter().all(|a| distance(node.x, node.y, a.x, a.y) > 1)
+}
+
+pub fn autopilot_mode(ship: &Ship, asteroids: &[Asteroid], resources: &[ResourceNode], fuel_reserve: f32) -> AutopilotMode {
+    let nearby = asteroids.iter().filter(|a| distance(ship.x, ship.y, a.x, a.y) <= SURROUND_RADIUS).count();
+    let surrounded = nearby >= SURROUND_COUNT;
+    let safe_mining_nearby = resources.iter()
+        .any(|r| distance(ship.x, ship.y, r.x, r.y) <= SAFE_MINING_RANGE && safe_node(r, asteroids));
+    let stranded = fuel_state(ship.total_fuel(), fuel_reserve) == FuelState::Critical && !safe_mining_nearby;
+    if surrounded || stranded || resources.is_empty() {
+        AutopilotMode::Flee
+    } else {
+        AutopilotMode::Seek
+    }
+}
+
+// Follows the danger gradient downhill: the adjacent move (or staying put)
+// that is furthest from the surrounding asteroids
+pub fn flee_move(ship: &Ship, asteroids: &[Asteroid], bounds: &Rect, arena: &Arena) -> InputEvent {
+    let mut best = (InputEvent::None, f32::INFINITY);
+    for input in MOVES {
+        if let Some((x, y)) = target_cell(ship, input, bounds, arena) {
+            let danger = danger_at(x, y, asteroids);
+            if danger < best.1 {
+                best = (input, danger);
+            }
+        }
+    }
+    best.0
+}
+
+// Heads for the nearest node without flying into an asteroid, and mines it on arrival
+fn seek_move(ship: &Ship, asteroids: &[Asteroid], resources: &[ResourceNode], bounds: &Rect, arena: &Arena) -> InputEvent {
+    let Some(node) = resources.iter().min_by_key(|r| distance(ship.x, ship.y, r.x, r.y)) else {
+        return flee_move(ship, asteroids, bounds, arena);
+    };
+    if (node.x, node.y) == (ship.x, ship.y) {
+        return InputEvent::Mine;
+    }
+    let mut best = None;
+    for input in MOVES {
+        let Some((x, y)) = target_cell(ship, input, bounds, arena) else { continue };
+        // Anywhere along the glyph would be a hit, not just the nose
+        if asteroids.iter().any(|a| a.y == y && (x..x + SHIP_WIDTH).contains(&a.x)) {
+            continue;
+        }
+        let d = x.abs_diff(node.x) + y.abs_diff(node.y);
+        if best.is_none_or(|(_, best_d)| d < best_d) {
+            best = Some((input, d));
+        }
+    }
+    best.map_or(InputEvent::None, |(input, _)| input)
+}
+
+pub fn autopilot(
+    ship: &Ship,
+    asteroids: &[Asteroid],
+    resources: &[ResourceNode],
+    bounds: &Rect,
+    arena: &Arena,
+    fuel_reserve: f32,
+) -> InputEvent {
+    match autopilot_mode(ship, asteroids, resources, fuel_reserve) {
+        AutopilotMode::Seek => seek_move(ship, asteroids, resources, bounds, arena),
+        AutopilotMode::Flee => flee_move(ship, asteroids, bounds, arena),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use crate::Resource;
+
+    const BOUNDS: Rect = Rect { x: 0, y: 0, w: 30, h: 15 };
+
+    fn rock(x: u16, y: u16) -> Asteroid {
+        Asteroid { x, y, vx: 0, vy: 0 }
+    }
+
+    #[test]
+    fn seeking_steers_the_whole_ship_clear_of_asteroids() {
+        let ship = Ship { x: 5, y: 5, ..Ship::new() };
+        let node = ResourceNode::new(5, 9, Resource::Iron, Duration::ZERO);
+        // Right below the ship's tail
+        let asteroids = [rock(7, 6)];
+        let input = seek_move(&ship, &asteroids, &[node], &BOUNDS, &Arena::default());
+        assert_ne!(input, InputEvent::Down);
+    }
+
+    #[test]
+    fn surrounded_ship_flees() {
+        let ship = Ship { x: 10, y: 7, ..Ship::new() };
+        let node = ResourceNode::new(20, 7, Resource::Iron, Duration::ZERO);
+        let asteroids = [rock(12, 7), rock(8, 7), rock(10, 5)];
+        assert_eq!(autopilot_mode(&ship, &asteroids, std::slice::from_ref(&node), 20.0), AutopilotMode::Flee);
+        assert_eq!(autopilot_mode(&ship, &asteroids[..2], &[node], 20.0), AutopilotMode::Seek);
+    }
+
+    #[test]
+    fn ship_low_on_fuel_flees_unless_a_safe_node_is_close() {
+        let ship = Ship { x: 10, y: 7, fuel: 10.0, ..Ship::new() };
+        let far = ResourceNode::new(25, 7, Resource::Iron, Duration::ZERO);
+        let near = ResourceNode::new(14, 7, Resource::Iron, Duration::ZERO);
+        assert_eq!(autopilot_mode(&ship, &[], &[far], 20.0), AutopilotMode::Flee);
+        assert_eq!(autopilot_mode(&ship, &[], std::slice::from_ref(&near), 20.0), AutopilotMode::Seek);
+        // An asteroid right next to the near node makes it unsafe
+        assert_eq!(autopilot_mode(&ship, &[rock(15, 7)], &[near], 20.0), AutopilotMode::Flee);
+    }
+
+    #[test]
+    fn fleeing_moves_away_from_the_danger() {
+        let ship = Ship { x: 10, y: 7, ..Ship::new() };
+        assert_eq!(flee_move(&ship, &[rock(11, 7)], &BOUNDS, &Arena::default()), InputEvent::Left);
+        assert_eq!(flee_move(&ship, &[rock(10, 8)], &BOUNDS, &Arena::default()), InputEvent::Up);
+        assert!(danger_at(0, 0, &[rock(1, 1)]) > danger_at(0, 0, &[rock(5, 5)]));
+    }
+}
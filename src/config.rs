@@ -0,0 +1,543 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use serde_json::{Map, Value};
+use crate::Resource;
+use crate::scoring::ScoringMode;
+use crate::storage;
+use crate::theme::Theme;
+use crate::upgrades::{self, UpgradeDef};
+
+pub const SETTINGS_FILE: &str = "settings.json";
+// Bump when a setting is renamed or changes shape, and add a migration for it
+pub const SETTINGS_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MilestoneEvent {
+    SupplyDrop,
+    MeteorShower,
+    DifficultyBump,
+    // Bonus round: resources pour in while asteroids hold still
+    ResourceRain,
+    // A block of asteroids sweeps in from one edge, announced in advance
+    Boss,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Milestone {
+    pub score: u32,
+    pub event: MilestoneEvent,
+}
+
+// A goal that ends the run in victory, e.g. {"Score": 500} or
+// {"Collect": {"resource": "Gold", "count": 5}}
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WinCondition {
+    Score(u32),
+    SurviveSecs(f32),
+    Collect { resource: Resource, count: u32 },
+}
+
+// How often the HUD line is redrawn, independently of the playfield
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HudRefresh {
+    EveryFrame,
+    EveryNFrames(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LevelType {
+    // Open asteroid field, free movement
+    Field,
+    // The field flows down past the ship, which only moves left and right
+    Scrolling,
+    // Race through checkpoint gates in order against the clock
+    TimeTrial,
+}
+
+impl FromStr for LevelType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "field" => Ok(LevelType::Field),
+            "scrolling" => Ok(LevelType::Scrolling),
+            "trial" => Ok(LevelType::TimeTrial),
+            _ => Err(()),
+        }
+    }
+}
+
+// Entity layers of the playfield, listed back to front in `Settings::draw_order`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Layer {
+    Ghost,
+    Resources,
+    Asteroids,
+    Drone,
+    Ships,
+}
+
+// How new asteroids appear in the open field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpawnPattern {
+    // One at a time, anywhere
+    Uniform,
+    // A few at once, bunched together
+    Clustered,
+}
+
+impl FromStr for SpawnPattern {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uniform" => Ok(SpawnPattern::Uniform),
+            "clustered" => Ok(SpawnPattern::Clustered),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl FromStr for Difficulty {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(Difficulty::Easy),
+            "normal" => Ok(Difficulty::Normal),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err(()),
+        }
+    }
+}
+
+// --- Settings ---
+// Loaded from settings.json (if present), then overridden by command-line flags.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // Format version of the file this was loaded from; older files are migrated
+    pub version: u32,
+    // Jump straight into play without the "Press any key" welcome screen
+    pub skip_intro: bool,
+    // Fixed RNG seed for reproducible runs; random when unset
+    pub seed: Option<u64>,
+    // Score thresholds that each fire their event once when crossed
+    pub milestones: Vec<Milestone>,
+    // Throttle HUD redraws on terminals that flicker
+    pub hud_refresh: HudRefresh,
+    // Fraction of the playfield covered by asteroids that counts as dense
+    pub dense_field_threshold: f32,
+    // Hold off spawning new asteroids while the field is dense
+    pub halt_spawns_when_dense: bool,
+    pub level: LevelType,
+    // Seconds an unmined resource node lasts before decaying; 0 keeps them forever
+    pub resource_lifetime_secs: f32,
+    pub difficulty: Difficulty,
+    // Overrides the difficulty's starting asteroid count
+    pub starting_asteroids: Option<u32>,
+    // Local co-op: a second ship on the arrow keys
+    pub two_player: bool,
+    // Accessible controls: the ship flies itself and SPACE does everything
+    pub one_button: bool,
+    // Blank cells between the border and the playfield, for roomier terminals
+    pub margin: u16,
+    // Cells around each asteroid that scrape the hull instead of destroying the
+    // ship; 0 means any contact is fatal
+    pub graze_radius: u16,
+    // Hull damage per tick for each asteroid being grazed
+    pub graze_damage: f32,
+    // Gentler mode: an asteroid strike costs hull and throws the ship
+    // `knockback_distance` cells clear instead of destroying it
+    pub knockback: bool,
+    pub knockback_distance: u16,
+    // Upgrades every ship starts the run with, by name
+    pub starting_upgrades: Vec<String>,
+    // Upgrades beyond the built-in ones; one with a built-in's name replaces it
+    pub upgrades: Vec<UpgradeDef>,
+    // Glyph sequences resources cycle through, e.g. "♦◇" for a twinkling crystal;
+    // resources without an entry use their usual glyph
+    pub resource_animations: HashMap<Resource, String>,
+    // Chance a spawned node is rich, holding `rich_node_amount` extractions
+    // instead of one
+    pub rich_node_chance: f64,
+    pub rich_node_amount: u32,
+    // Chance a spawned node is contaminated and costs fuel to mine
+    pub contamination_chance: f64,
+    // Credits each resource is worth
+    pub resource_values: HashMap<Resource, u32>,
+    // Terminal-bell cues
+    pub sound: bool,
+    // Mining beeps once, plus once per threshold the resource's value reaches
+    pub sound_value_tiers: Vec<u32>,
+    // No asteroids and an endless supply of resources; stats and score still count
+    pub practice: bool,
+    // Where to write a JSON summary of the run when it ends
+    pub stats_out: Option<PathBuf>,
+    // Append every run's summary to history.jsonl; off unless asked for
+    pub history: bool,
+    // Magnet pull in cells per tick on a node one cell away; weaker further out
+    pub magnet_strength: f32,
+    // Seconds the game-over screen stays up before closing by itself, for
+    // demos and scripted runs; unset waits for a keypress
+    pub game_over_timeout_secs: Option<f32>,
+    // End the run after this many ticks or seconds of play, for demos and CI
+    pub max_ticks: Option<u32>,
+    pub max_seconds: Option<f32>,
+    pub theme: Theme,
+    // Seconds of play between each difficulty increase; 0 turns the ramp off
+    pub difficulty_ramp_secs: f32,
+    // How long a resource rain bonus round lasts
+    pub resource_rain_secs: f32,
+    // How long the warning before a boss lasts
+    pub boss_warning_secs: f32,
+    // Level file of wall cells shaping the playfield
+    pub arena: Option<PathBuf>,
+    // Mine any node the ship is sitting on without holding the mine key
+    pub auto_mine: bool,
+    // Reaching this wins the run; without one the run lasts until the ship is lost
+    pub win_condition: Option<WinCondition>,
+    // Local TCP port streaming the game to spectators
+    pub spectate_port: Option<u16>,
+    // Debug overlay marking the cells collisions are checked against
+    pub show_hitboxes: bool,
+    // Fuel the station gives for each unit of cargo traded in
+    pub fuel_exchange_rate: f32,
+    // Advice on the game-over screen based on how the run ended
+    pub hints: bool,
+    // Fuel level at which low-fuel warnings start: the ship and fuel bar turn
+    // red and the autopilot stops risking long trips
+    pub fuel_reserve: f32,
+    // Main tank size, plus a reserve tank that only drains once the main one
+    // is empty and refills at `reserve_refill_rate` per unit of fuel overflowing
+    // the main tank; a reserve of 0 means none
+    pub fuel_capacity: f32,
+    pub reserve_tank: f32,
+    pub reserve_refill_rate: f32,
+    pub asteroid_spawn: SpawnPattern,
+    pub scoring: ScoringMode,
+    // Back-to-front order entity layers are drawn in, so later ones show where
+    // entities overlap; layers left out aren't drawn at all
+    pub draw_order: Vec<Layer>,
+    // Nodes yield one unit, give or take up to this many; 0 keeps yields exact
+    pub yield_variance: u32,
+    // Fuel each kind of node tops the tank up by when mined
+    pub fuel_values: HashMap<Resource, f32>,
+    // Overrides the difficulty curve's multiplier on `fuel_values` for the
+    // difficulties listed
+    pub fuel_value_scale: HashMap<Difficulty, f32>,
+    // (width, height) of a world bigger than the screen, which scrolls to
+    // follow the ship; unset fits the world to the screen
+    pub world_size: Option<(u16, u16)>,
+    // (width, height) of the box in the middle of the screen the ship can
+    // roam before the view scrolls after it
+    pub camera_deadzone: (u16, u16),
+    // Save slot the run's credits are banked to
+    pub save_slot: u8,
+    // Losing the ship deletes the save slot, career credits and all
+    pub hardcore: bool,
+    // Twinkling stars in the empty parts of the playfield
+    pub starfield: bool,
+    // Low-powered terminals: cosmetic effects off and fewer redraws
+    pub perf: bool,
+    // Milliseconds movement keys are ignored for after a node is mined
+    pub mine_dead_time_ms: u64,
+    // Kiosk mode: seconds idle on the title screen before it starts cycling
+    // the high scores and a demo run
+    pub attract_idle_secs: Option<f32>,
+    // Cells between a ship and any asteroid spawned mid-run; 1 just keeps
+    // them off the ship itself
+    pub min_spawn_distance: u16,
+    // Units of cargo each ship holds, drone hauls included; unset is unlimited
+    pub cargo_capacity: Option<u32>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            version: SETTINGS_VERSION,
+            skip_intro: false,
+            seed: None,
+            milestones: vec![
+                Milestone { score: 50, event: MilestoneEvent::SupplyDrop },
+                Milestone { score: 100, event: MilestoneEvent::MeteorShower },
+                Milestone { score: 150, event: MilestoneEvent::ResourceRain },
+                Milestone { score: 200, event: MilestoneEvent::DifficultyBump },
+                Milestone { score: 300, event: MilestoneEvent::MeteorShower },
+                Milestone { score: 400, event: MilestoneEvent::Boss },
+            ],
+            hud_refresh: HudRefresh::EveryFrame,
+            dense_field_threshold: 0.25,
+            halt_spawns_when_dense: true,
+            level: LevelType::Field,
+            resource_lifetime_secs: 0.0,
+            difficulty: Difficulty::Normal,
+            starting_asteroids: None,
+            two_player: false,
+            one_button: false,
+            margin: 0,
+            graze_radius: 0,
+            graze_damage: 25.0,
+            knockback: false,
+            knockback_distance: 2,
+            starting_upgrades: Vec::new(),
+            upgrades: Vec::new(),
+            resource_animations: HashMap::from([(Resource::Crystal, "♦◇".to_string())]),
+            rich_node_chance: 0.0,
+            rich_node_amount: 3,
+            contamination_chance: 0.0,
+            resource_values: HashMap::from([
+                (Resource::Iron, 10),
+                (Resource::Crystal, 20),
+                (Resource::Gold, 50),
+            ]),
+            sound: false,
+            sound_value_tiers: vec![20, 50],
+            practice: false,
+            stats_out: None,
+            history: false,
+            magnet_strength: 1.0,
+            game_over_timeout_secs: None,
+            max_ticks: None,
+            max_seconds: None,
+            theme: Theme::default(),
+            difficulty_ramp_secs: 40.0,
+            resource_rain_secs: 8.0,
+            boss_warning_secs: 3.0,
+            arena: None,
+            auto_mine: false,
+            win_condition: None,
+            spectate_port: None,
+            show_hitboxes: false,
+            fuel_exchange_rate: 10.0,
+            hints: true,
+            fuel_reserve: 20.0,
+            fuel_capacity: 100.0,
+            reserve_tank: 0.0,
+            reserve_refill_rate: 0.5,
+            asteroid_spawn: SpawnPattern::Uniform,
+            scoring: ScoringMode::Standard,
+            draw_order: vec![Layer::Ghost, Layer::Resources, Layer::Asteroids, Layer::Drone, Layer::Ships],
+            yield_variance: 0,
+            fuel_values: HashMap::from([(Resource::Crystal, 20.0)]),
+            fuel_value_scale: HashMap::new(),
+            world_size: None,
+            camera_deadzone: (12, 5),
+            save_slot: 1,
+            hardcore: false,
+            starfield: false,
+            perf: false,
+            mine_dead_time_ms: 150,
+            attract_idle_secs: None,
+            min_spawn_distance: 1,
+            cargo_capacity: None,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load(path: &Path) -> Result<Settings, String> {
+        storage::load_with(path, Settings::parse)
+    }
+
+    // Brings older files up to date before reading them; fields a file doesn't
+    // mention at all just take their defaults
+    pub fn parse(text: &str) -> Result<Settings, String> {
+        let mut value: Value = serde_json::from_str(text).map_err(|e| e.to_string())?;
+        let fields = value.as_object_mut().ok_or("expected a JSON object")?;
+        let version = match fields.get("version") {
+            Some(v) => v.as_u64().ok_or("version must be a number")? as u32,
+            None => 0,
+        };
+        if version > SETTINGS_VERSION {
+            return Err(format!("version {} is newer than this game supports ({})", version, SETTINGS_VERSION));
+        }
+        for migrate in &MIGRATIONS[version as usize..] {
+            migrate(fields);
+        }
+        fields.insert("version".to_string(), Value::from(SETTINGS_VERSION));
+        serde_json::from_value(value).map_err(|e| e.to_string())
+    }
+
+    // Starfield, ghost trails, shimmer and animation; none of them affect play
+    pub fn effects_enabled(&self) -> bool {
+        !self.perf
+    }
+
+    // Fuel mining a node of `kind` gives, with the difficulty's `scale` applied
+    pub fn fuel_value(&self, kind: Resource, scale: f32) -> f32 {
+        self.fuel_values.get(&kind).copied().unwrap_or(0.0) * scale
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.fuel_capacity <= 0.0 {
+            return Err(format!("fuel_capacity must be above 0, got {}", self.fuel_capacity));
+        }
+        // A distance of 0 would let asteroids spawn right on a ship
+        if self.min_spawn_distance == 0 {
+            return Err("min_spawn_distance must be at least 1".to_string());
+        }
+        for name in &self.starting_upgrades {
+            if upgrades::find_upgrade(name, &self.upgrades).is_none() {
+                return Err(format!("unknown upgrade {:?} in starting_upgrades", name));
+            }
+        }
+        self.theme.validate()
+    }
+
+    pub fn apply_args<I: IntoIterator<Item = String>>(&mut self, args: I) -> Result<(), String> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--skip-intro" => self.skip_intro = true,
+                "--two-player" => self.two_player = true,
+                "--one-button" => self.one_button = true,
+                "--sound" => self.sound = true,
+                "--practice" => self.practice = true,
+                "--gold-rush" => self.scoring = ScoringMode::GoldRush,
+                "--hardcore" => self.hardcore = true,
+                "--slot" => self.save_slot = parse_value(&arg, args.next())?,
+                "--attract" => self.attract_idle_secs = Some(parse_value(&arg, args.next())?),
+                "--perf" => self.perf = true,
+                "--stars" => self.starfield = true,
+                "--show-ore" => self.draw_order = vec![Layer::Ghost, Layer::Asteroids, Layer::Resources, Layer::Drone, Layer::Ships],
+                "--auto-mine" => self.auto_mine = true,
+                "--history" => self.history = true,
+                "--show-hitboxes" => self.show_hitboxes = true,
+                "--no-hints" => self.hints = false,
+                "--arena" => self.arena = Some(parse_value(&arg, args.next())?),
+                "--stats-out" => self.stats_out = Some(parse_value(&arg, args.next())?),
+                "--game-over-timeout" => self.game_over_timeout_secs = Some(parse_value(&arg, args.next())?),
+                "--win-score" => self.win_condition = Some(WinCondition::Score(parse_value(&arg, args.next())?)),
+                "--win-time" => self.win_condition = Some(WinCondition::SurviveSecs(parse_value(&arg, args.next())?)),
+                "--spectate" => self.spectate_port = Some(parse_value(&arg, args.next())?),
+                "--max-ticks" => self.max_ticks = Some(parse_value(&arg, args.next())?),
+                "--max-seconds" => self.max_seconds = Some(parse_value(&arg, args.next())?),
+                "--seed" => self.seed = Some(parse_value(&arg, args.next())?),
+                "--knockback" => {
+                    self.knockback = true;
+                    self.knockback_distance = parse_value(&arg, args.next())?;
+                }
+                "--spawn-distance" => self.min_spawn_distance = parse_value(&arg, args.next())?,
+                "--fuel-reserve" => self.fuel_reserve = parse_value(&arg, args.next())?,
+                "--reserve-tank" => self.reserve_tank = parse_value(&arg, args.next())?,
+                "--cargo-capacity" => self.cargo_capacity = Some(parse_value(&arg, args.next())?),
+                "--yield-variance" => self.yield_variance = parse_value(&arg, args.next())?,
+                "--margin" => self.margin = parse_value(&arg, args.next())?,
+                "--level" => self.level = parse_value(&arg, args.next())?,
+                "--spawn" => self.asteroid_spawn = parse_value(&arg, args.next())?,
+                "--difficulty" => self.difficulty = parse_value(&arg, args.next())?,
+                other => return Err(format!("unknown argument: {}", other)),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn game_over_timeout(&self) -> Option<Duration> {
+        self.game_over_timeout_secs.map(|s| Duration::from_secs_f32(s.max(0.0)))
+    }
+
+    pub fn attract_idle(&self) -> Option<Duration> {
+        self.attract_idle_secs.map(|s| Duration::from_secs_f32(s.max(0.0)))
+    }
+}
+
+// --- Migrations ---
+// MIGRATIONS[n] upgrades a version n file to version n + 1
+const MIGRATIONS: [fn(&mut Map<String, Value>); SETTINGS_VERSION as usize] = [
+    // Unversioned files only lack fields added since, which defaults cover
+    |_| {},
+];
+
+fn parse_value<T: FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{} needs a value", flag))?;
+    value.parse().map_err(|_| format!("invalid value for {}: {}", flag, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negative_or_nan_attract_timeout_is_clamped() {
+        for secs in [-1.0, f32::NAN] {
+            let settings = Settings { attract_idle_secs: Some(secs), ..Settings::default() };
+            assert_eq!(settings.attract_idle(), Some(Duration::ZERO));
+        }
+    }
+
+    #[test]
+    fn unversioned_file_keeps_its_values_and_defaults_the_rest() {
+        let settings = Settings::parse(r#"{"margin": 3, "difficulty": "Hard", "skip_intro": true}"#).unwrap();
+        assert_eq!(settings.version, SETTINGS_VERSION);
+        assert_eq!(settings.margin, 3);
+        assert_eq!(settings.difficulty, Difficulty::Hard);
+        assert!(settings.skip_intro);
+        // Fields added since old files were written take their defaults
+        let defaults = Settings::default();
+        assert_eq!(settings.min_spawn_distance, defaults.min_spawn_distance);
+        assert_eq!(settings.attract_idle_secs, defaults.attract_idle_secs);
+        assert_eq!(settings.difficulty_ramp_secs, defaults.difficulty_ramp_secs);
+    }
+
+    #[test]
+    fn file_from_a_newer_game_is_refused() {
+        let text = format!(r#"{{"version": {}}}"#, SETTINGS_VERSION + 1);
+        assert!(Settings::parse(&text).is_err());
+    }
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn skip_intro_comes_from_the_file_or_the_flag() {
+        assert!(!Settings::default().skip_intro);
+        assert!(Settings::parse(r#"{"skip_intro": true}"#).unwrap().skip_intro);
+        let mut settings = Settings::default();
+        settings.apply_args(args(&["--skip-intro"])).unwrap();
+        assert!(settings.skip_intro);
+    }
+
+    #[test]
+    fn missing_settings_file_uses_the_defaults() {
+        let path = std::env::temp_dir().join(format!("rusty-space-miner-{}-no-settings.json", std::process::id()));
+        let settings = Settings::load(&path).unwrap();
+        assert_eq!(settings.skip_intro, Settings::default().skip_intro);
+    }
+
+    #[test]
+    fn unknown_flag_is_an_error() {
+        assert!(Settings::default().apply_args(args(&["--warp-speed"])).is_err());
+        assert!(Settings::default().apply_args(args(&["--seed"])).is_err());
+    }
+
+    #[test]
+    fn game_over_timeout_is_off_unless_set() {
+        assert_eq!(Settings::default().game_over_timeout(), None);
+        let mut settings = Settings::default();
+        settings.apply_args(args(&["--game-over-timeout", "2.5"])).unwrap();
+        assert_eq!(settings.game_over_timeout(), Some(Duration::from_millis(2500)));
+        settings.game_over_timeout_secs = Some(-3.0);
+        assert_eq!(settings.game_over_timeout(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn spawn_distance_of_zero_is_refused() {
+        let mut settings = Settings::default();
+        settings.apply_args(args(&["--spawn-distance", "0"])).unwrap();
+        assert!(settings.validate().is_err());
+        settings.apply_args(args(&["--spawn-distance", "1"])).unwrap();
+        assert!(settings.validate().is_ok());
+    }
+}
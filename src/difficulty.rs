@@ -0,0 +1,169 @@
+use std::time::Duration;
+use crate::config::{Difficulty, Settings};
+
+// --- Difficulty Curve ---
+// Ticks between asteroid spawns at the start of a run
+const BASE_SPAWN_RATE: u32 = 50;
+// Each ramp step spawns asteroids this many ticks sooner, down to MIN_SPAWN_RATE
+const SPAWN_RATE_STEP: u32 = 5;
+const MIN_SPAWN_RATE: u32 = 10;
+// Fuel burnt per tick at the start of a run
+const BASE_FUEL_DRAIN: f32 = 0.5;
+// Drain never climbs past this, however long the run goes on
+const MAX_FUEL_DRAIN: f32 = 2.0;
+
+// What an asteroid strike does to a ship
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CollisionMode {
+    Fatal,
+    // Costs hull and throws the ship this many cells clear
+    Knockback(u16),
+}
+
+// Everything the difficulty decides about a run at one point in it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DifficultyParams {
+    pub starting_asteroids: u32,
+    // Ramp steps taken so far
+    pub ramp_steps: u32,
+    // Ticks between asteroid spawns; lower is faster
+    pub spawn_rate: u32,
+    // Fuel burnt per tick
+    pub fuel_drain: f32,
+    // Share of a node's base fuel value the ship gets for mining it
+    pub fuel_value_scale: f32,
+    // Percent of the base points each mine scores
+    pub score_percent: u32,
+    pub collision: CollisionMode,
+    // Share of the field asteroids may cover before spawning pauses; None
+    // never pauses
+    pub asteroid_cap: Option<f32>,
+}
+
+// Spawn rate after `steps` ramp steps (or difficulty bumps)
+fn spawn_rate_after(steps: u32) -> u32 {
+    BASE_SPAWN_RATE.saturating_sub(SPAWN_RATE_STEP.saturating_mul(steps)).max(MIN_SPAWN_RATE)
+}
+
+// The difficulty curve `elapsed` into a run, with the `bumps` milestones have
+// added and anything the settings override. The ramp steps up once every
+// `difficulty_ramp_secs` of game time; zero turns it off.
+pub fn resolve_difficulty(settings: &Settings, elapsed: Duration, bumps: u32) -> DifficultyParams {
+    let difficulty = settings.difficulty;
+    let ramp_interval = settings.difficulty_ramp_secs.max(0.0) as f64;
+    let ramp_steps = if ramp_interval == 0.0 {
+        0
+    } else {
+        (elapsed.as_secs_f64() / ramp_interval) as u32
+    };
+    // Extra fuel drain per tick gained for every minute survived
+    let drain_growth = match difficulty {
+        Difficulty::Easy => 0.0,
+        Difficulty::Normal => 0.1,
+        Difficulty::Hard => 0.25,
+    };
+    let minutes = elapsed.as_secs_f32() / 60.0;
+    let starting_asteroids = settings.starting_asteroids.unwrap_or(match difficulty {
+        Difficulty::Easy => 2,
+        Difficulty::Normal => 3,
+        Difficulty::Hard => 6,
+    });
+    let fuel_value_scale = settings.fuel_value_scale.get(&difficulty).copied().unwrap_or(match difficulty {
+        Difficulty::Easy => 1.0,
+        Difficulty::Normal => 0.85,
+        Difficulty::Hard => 0.7,
+    });
+    DifficultyParams {
+        // Practice mode has no asteroids at all
+        starting_asteroids: if settings.practice { 0 } else { starting_asteroids },
+        ramp_steps,
+        spawn_rate: spawn_rate_after(ramp_steps + bumps),
+        fuel_drain: (BASE_FUEL_DRAIN + drain_growth * minutes).min(MAX_FUEL_DRAIN),
+        fuel_value_scale,
+        score_percent: match difficulty {
+            Difficulty::Easy | Difficulty::Normal => 100,
+            Difficulty::Hard => 150,
+        },
+        collision: if settings.knockback {
+            CollisionMode::Knockback(settings.knockback_distance)
+        } else {
+            CollisionMode::Fatal
+        },
+        asteroid_cap: if settings.practice {
+            Some(0.0)
+        } else {
+            settings.halt_spawns_when_dense.then_some(settings.dense_field_threshold)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(difficulty: Difficulty, secs: u64) -> DifficultyParams {
+        let settings = Settings { difficulty, ..Settings::default() };
+        resolve_difficulty(&settings, Duration::from_secs(secs), 0)
+    }
+
+    #[test]
+    fn run_starts_at_the_base_rates() {
+        for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+            let params = at(difficulty, 0);
+            assert_eq!(params.ramp_steps, 0);
+            assert_eq!(params.spawn_rate, BASE_SPAWN_RATE);
+            assert_eq!(params.fuel_drain, BASE_FUEL_DRAIN);
+            assert_eq!(params.collision, CollisionMode::Fatal);
+        }
+        assert_eq!(at(Difficulty::Easy, 0).starting_asteroids, 2);
+        assert_eq!(at(Difficulty::Normal, 0).starting_asteroids, 3);
+        assert_eq!(at(Difficulty::Hard, 0).starting_asteroids, 6);
+        assert_eq!(at(Difficulty::Hard, 0).score_percent, 150);
+        assert_eq!(at(Difficulty::Hard, 0).fuel_value_scale, 0.7);
+    }
+
+    #[test]
+    fn ramp_speeds_spawns_up_to_a_floor() {
+        // The default ramp steps up every 40 seconds
+        let params = at(Difficulty::Normal, 120);
+        assert_eq!(params.ramp_steps, 3);
+        assert_eq!(params.spawn_rate, BASE_SPAWN_RATE - 3 * SPAWN_RATE_STEP);
+        assert_eq!(at(Difficulty::Normal, 3600).spawn_rate, MIN_SPAWN_RATE);
+    }
+
+    #[test]
+    fn drain_grows_with_difficulty_up_to_a_ceiling() {
+        assert_eq!(at(Difficulty::Easy, 600).fuel_drain, BASE_FUEL_DRAIN);
+        assert!((at(Difficulty::Normal, 120).fuel_drain - 0.7).abs() < 1e-4);
+        assert!((at(Difficulty::Hard, 120).fuel_drain - 1.0).abs() < 1e-4);
+        assert_eq!(at(Difficulty::Hard, 3600).fuel_drain, MAX_FUEL_DRAIN);
+    }
+
+    #[test]
+    fn bumps_and_overrides_feed_the_resolved_params() {
+        let mut settings = Settings {
+            difficulty: Difficulty::Hard,
+            starting_asteroids: Some(9),
+            knockback: true,
+            difficulty_ramp_secs: 0.0,
+            ..Settings::default()
+        };
+        settings.fuel_value_scale.insert(Difficulty::Hard, 0.5);
+        let params = resolve_difficulty(&settings, Duration::from_secs(600), 2);
+        assert_eq!(params.ramp_steps, 0);
+        assert_eq!(params.spawn_rate, BASE_SPAWN_RATE - 2 * SPAWN_RATE_STEP);
+        assert_eq!(params.starting_asteroids, 9);
+        assert_eq!(params.fuel_value_scale, 0.5);
+        assert_eq!(params.collision, CollisionMode::Knockback(settings.knockback_distance));
+    }
+
+    #[test]
+    fn practice_has_no_asteroids() {
+        let settings = Settings { practice: true, ..Settings::default() };
+        let params = resolve_difficulty(&settings, Duration::ZERO, 0);
+        assert_eq!(params.starting_asteroids, 0);
+        assert_eq!(params.asteroid_cap, Some(0.0));
+        let open = Settings { halt_spawns_when_dense: false, ..Settings::default() };
+        assert_eq!(resolve_difficulty(&open, Duration::ZERO, 0).asteroid_cap, None);
+    }
+}
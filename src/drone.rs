@@ -0,0 +1,168 @@
+use crate::{deliver, mine_duration, Resource, ResourceNode, Ship};
+use crate::config::Settings;
+
+// --- Collector Drone ---
+// A companion that flies out to the nearest resource node, mines it, and
+// brings the haul back to the ship by itself. It shares the ship's cargo
+// capacity and stays home while the hold is full.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DroneState {
+    // Flying to the nearest node
+    Seek,
+    // Parked on a node, `progress` ticks in
+    Mine,
+    // Carrying a mined resource back to the ship
+    Return(Resource),
+}
+
+#[derive(Debug, Clone)]
+pub struct Drone {
+    pub x: u16,
+    pub y: u16,
+    pub state: DroneState,
+    progress: u32,
+}
+
+impl Drone {
+    pub fn new(x: u16, y: u16) -> Self {
+        Drone { x, y, state: DroneState::Seek, progress: 0 }
+    }
+
+    // One cell towards the target per tick, closing the longer gap first
+    fn step_toward(&mut self, x: u16, y: u16) {
+        if self.x.abs_diff(x) >= self.y.abs_diff(y) {
+            if self.x < x { self.x += 1 } else if self.x > x { self.x -= 1 }
+        } else if self.y < y {
+            self.y += 1;
+        } else {
+            self.y -= 1;
+        }
+    }
+}
+
+// Advances the drone one tick; returns the resource it hands over to the ship, if any
+pub fn drone_system(
+    drone: &mut Drone,
+    ship: &mut Ship,
+    resources: &mut Vec<ResourceNode>,
+    settings: &Settings,
+    fuel_scale: f32,
+) -> Option<Resource> {
+    match drone.state {
+        DroneState::Seek => {
+            // The drone hauls into the ship's hold, so a full hold grounds it
+            let nearest = resources.iter()
+                .min_by_key(|r| drone.x.abs_diff(r.x) + drone.y.abs_diff(r.y))
+                .map(|r| (r.x, r.y))
+                .filter(|_| ship.cargo_room() > 0);
+            match nearest {
+                Some((x, y)) if (x, y) == (drone.x, drone.y) => {
+                    drone.state = DroneState::Mine;
+                    drone.progress = 0;
+                }
+                Some((x, y)) => drone.step_toward(x, y),
+                // Nothing to collect or nowhere to put it, so stay with the ship
+                None => drone.step_toward(ship.x, ship.y),
+            }
+            None
+        }
+        DroneState::Mine => {
+            // Someone else may have mined the node out from under the drone
+            let Some(idx) = resources.iter().position(|r| (r.x, r.y) == (drone.x, drone.y)) else {
+                drone.state = DroneState::Seek;
+                return None;
+            };
+            drone.progress += 1;
+            if drone.progress >= mine_duration(resources[idx].kind, &[]) {
+                // Rich nodes give one unit per trip, like they do the ship
+                let kind = resources[idx].kind;
+                resources[idx].amount -= 1;
+                if resources[idx].amount == 0 {
+                    resources.remove(idx);
+                }
+                drone.state = DroneState::Return(kind);
+            }
+            None
+        }
+        DroneState::Return(kind) => {
+            if (drone.x, drone.y) != (ship.x, ship.y) {
+                drone.step_toward(ship.x, ship.y);
+            }
+            if (drone.x, drone.y) != (ship.x, ship.y) {
+                return None;
+            }
+            deliver(ship, kind, 1, settings.fuel_value(kind, fuel_scale));
+            drone.state = DroneState::Seek;
+            Some(kind)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn tick(drone: &mut Drone, ship: &mut Ship, resources: &mut Vec<ResourceNode>) {
+        drone_system(drone, ship, resources, &Settings::default(), 1.0);
+    }
+
+    #[test]
+    fn drone_heads_for_the_nearest_node() {
+        let mut drone = Drone::new(4, 4);
+        let mut ship = Ship::new();
+        let mut resources = vec![
+            ResourceNode::new(20, 4, Resource::Iron, Duration::ZERO),
+            ResourceNode::new(4, 8, Resource::Iron, Duration::ZERO),
+        ];
+        tick(&mut drone, &mut ship, &mut resources);
+        assert_eq!((drone.x, drone.y), (4, 5));
+        assert_eq!(drone.state, DroneState::Seek);
+    }
+
+    #[test]
+    fn drone_mines_a_node_and_brings_it_to_the_ship() {
+        let mut drone = Drone::new(10, 8);
+        let mut ship = Ship::new();
+        let mut resources = vec![ResourceNode::new(10, 8, Resource::Gold, Duration::ZERO)];
+        tick(&mut drone, &mut ship, &mut resources);
+        assert_eq!(drone.state, DroneState::Mine);
+        for _ in 0..Resource::Gold.hardness() {
+            tick(&mut drone, &mut ship, &mut resources);
+        }
+        assert_eq!(drone.state, DroneState::Return(Resource::Gold));
+        assert!(resources.is_empty());
+        // Two cells back to the ship at (10, 10), handing over on arrival
+        tick(&mut drone, &mut ship, &mut resources);
+        assert_eq!(ship.cargo[&Resource::Gold], 0);
+        tick(&mut drone, &mut ship, &mut resources);
+        assert_eq!(ship.cargo[&Resource::Gold], 1);
+        assert_eq!(drone.state, DroneState::Seek);
+    }
+
+    #[test]
+    fn full_hold_keeps_the_drone_with_the_ship() {
+        let mut drone = Drone::new(10, 8);
+        let mut ship = Ship { cargo_capacity: Some(2), ..Ship::new() };
+        ship.cargo.insert(Resource::Iron, 2);
+        let mut resources = vec![ResourceNode::new(10, 8, Resource::Iron, Duration::ZERO)];
+        tick(&mut drone, &mut ship, &mut resources);
+        assert_eq!(drone.state, DroneState::Seek);
+        assert_eq!((drone.x, drone.y), (10, 9));
+        assert_eq!(resources.len(), 1);
+    }
+
+    #[test]
+    fn drone_takes_one_unit_from_a_rich_node() {
+        let mut drone = Drone::new(4, 4);
+        let mut ship = Ship::new();
+        let mut resources = vec![ResourceNode::new(4, 4, Resource::Iron, Duration::ZERO).rich(3)];
+        let settings = Settings::default();
+        drone_system(&mut drone, &mut ship, &mut resources, &settings, 1.0);
+        drone_system(&mut drone, &mut ship, &mut resources, &settings, 1.0);
+        assert_eq!(drone.state, DroneState::Return(Resource::Iron));
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0].amount, 2);
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{check_collision, Rect};
+
+// --- Geometry ---
+// Grid helpers shared by movement, spawning and wrapping. Points are taken as
+// i32 so callers can step off the edge (or below zero) and let these sort it out.
+
+pub fn in_bounds(x: i32, y: i32, bounds: &Rect) -> bool {
+    let (left, top) = (bounds.x as i32, bounds.y as i32);
+    x >= left && x < left + bounds.w as i32 && y >= top && y < top + bounds.h as i32
+}
+
+// The nearest cell inside `bounds`
+pub fn clamp_point(x: i32, y: i32, bounds: &Rect) -> (u16, u16) {
+    let (left, top) = (bounds.x as i32, bounds.y as i32);
+    let x = x.clamp(left, left + bounds.w as i32 - 1);
+    let y = y.clamp(top, top + bounds.h as i32 - 1);
+    (x as u16, y as u16)
+}
+
+// Leaving one edge of `bounds` comes back in at the opposite one
+pub fn wrap_point(x: i32, y: i32, bounds: &Rect) -> (u16, u16) {
+    let x = (x - bounds.x as i32).rem_euclid(bounds.w as i32);
+    let y = (y - bounds.y as i32).rem_euclid(bounds.h as i32);
+    (bounds.x + x as u16, bounds.y + y as u16)
+}
+
+pub fn rect_overlap(a: &Rect, b: &Rect) -> bool {
+    check_collision(a, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDS: Rect = Rect { x: 2, y: 1, w: 5, h: 4 };
+
+    #[test]
+    fn in_bounds_covers_exactly_the_rect() {
+        assert!(in_bounds(2, 1, &BOUNDS));
+        assert!(in_bounds(6, 4, &BOUNDS));
+        assert!(!in_bounds(7, 4, &BOUNDS));
+        assert!(!in_bounds(1, 1, &BOUNDS));
+        assert!(!in_bounds(3, -1, &BOUNDS));
+    }
+
+    #[test]
+    fn clamp_pulls_points_to_the_nearest_edge() {
+        assert_eq!(clamp_point(4, 2, &BOUNDS), (4, 2));
+        assert_eq!(clamp_point(-5, 2, &BOUNDS), (2, 2));
+        assert_eq!(clamp_point(100, 100, &BOUNDS), (6, 4));
+    }
+
+    #[test]
+    fn wrap_comes_back_in_at_the_opposite_edge() {
+        assert_eq!(wrap_point(7, 2, &BOUNDS), (2, 2));
+        assert_eq!(wrap_point(1, 2, &BOUNDS), (6, 2));
+        assert_eq!(wrap_point(3, 0, &BOUNDS), (3, 4));
+        assert_eq!(wrap_point(3, 5, &BOUNDS), (3, 1));
+    }
+
+    #[test]
+    fn rects_overlap_only_when_they_share_a_cell() {
+        let cell = |x, y| Rect { x, y, w: 1, h: 1 };
+        assert!(rect_overlap(&BOUNDS, &cell(6, 4)));
+        assert!(!rect_overlap(&BOUNDS, &cell(7, 4)));
+        assert!(!rect_overlap(&BOUNDS, &cell(2, 0)));
+    }
+}
@@ -0,0 +1,78 @@
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use crate::storage;
+
+pub const GHOST_FILE: &str = "ghost.json";
+
+// How many earlier positions trail behind the ghost ship
+const TRAIL_LENGTH: usize = 5;
+
+// --- Ghost ---
+// The ship's route from the last run, replayed next to the player on a run
+// with the same seed so routes can be compared
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Ghost {
+    pub seed: u64,
+    // Ship position at each tick, starting with the spawn point
+    pub path: Vec<(u16, u16)>,
+}
+
+impl Ghost {
+    pub fn load(path: &Path) -> Result<Option<Ghost>, String> {
+        storage::load_or_default(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        storage::save_compact(self, path)
+    }
+
+    // Where the ghost ship was at `tick`, once its recorded run has ended it's gone
+    pub fn position(&self, tick: u32) -> Option<(u16, u16)> {
+        self.path.get(tick as usize).copied()
+    }
+
+    // Positions leading up to `tick`, oldest first
+    pub fn trail(&self, tick: u32) -> &[(u16, u16)] {
+        let end = (tick as usize).min(self.path.len());
+        &self.path[end.saturating_sub(TRAIL_LENGTH)..end]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ghost() -> Ghost {
+        Ghost { seed: 7, path: (0..8).map(|x| (x, 3)).collect() }
+    }
+
+    #[test]
+    fn ghost_replays_its_path_then_is_gone() {
+        let ghost = ghost();
+        assert_eq!(ghost.position(0), Some((0, 3)));
+        assert_eq!(ghost.position(7), Some((7, 3)));
+        assert_eq!(ghost.position(8), None);
+    }
+
+    #[test]
+    fn trail_is_the_last_few_positions() {
+        let ghost = ghost();
+        assert!(ghost.trail(0).is_empty());
+        assert_eq!(ghost.trail(2), &[(0, 3), (1, 3)]);
+        assert_eq!(ghost.trail(7).len(), TRAIL_LENGTH);
+        assert_eq!(ghost.trail(7).last(), Some(&(6, 3)));
+        // Past the end of the run the trail stops growing
+        assert_eq!(ghost.trail(100), ghost.trail(8));
+    }
+
+    #[test]
+    fn saved_ghost_loads_back() {
+        let path = std::env::temp_dir().join(format!("rusty-space-miner-{}-ghost.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        assert!(Ghost::load(&path).unwrap().is_none());
+        ghost().save(&path).unwrap();
+        let loaded = Ghost::load(&path).unwrap().unwrap();
+        assert_eq!((loaded.seed, loaded.path), (7, ghost().path));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,132 @@
+use std::path::Path;
+use serde::{Serialize, Deserialize};
+use crate::storage;
+
+pub const HIGHSCORES_FILE: &str = "highscores.json";
+const MAX_ENTRIES: usize = 10;
+
+// --- Verification ---
+// A checksum over the run so hand-edited scores stand out. Anyone reading this
+// source can forge one, so it only flags casual edits; it is not tamper-proof.
+const TOKEN_SALT: &[u8] = b"rusty-space-miner";
+
+// FNV-1a, so tokens stay stable across Rust versions (unlike DefaultHasher)
+fn fnv1a(chunks: &[&[u8]]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for chunk in chunks {
+        for byte in *chunk {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+    hash
+}
+
+pub fn verification_token(score: u32, seed: u64, inputs: &str) -> String {
+    let hash = fnv1a(&[TOKEN_SALT, &seed.to_le_bytes(), &score.to_le_bytes(), inputs.as_bytes()]);
+    format!("{:016x}", hash)
+}
+
+// --- High Scores ---
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighScore {
+    pub score: u32,
+    pub seed: u64,
+    // One character per tick of input, two when both players acted, see
+    // `InputEvent::code`
+    pub inputs: String,
+    pub token: String,
+}
+
+impl HighScore {
+    pub fn new(score: u32, seed: u64, inputs: String) -> Self {
+        let token = verification_token(score, seed, &inputs);
+        HighScore { score, seed, inputs, token }
+    }
+
+    pub fn is_verified(&self) -> bool {
+        self.token == verification_token(self.score, self.seed, &self.inputs)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HighScoreBoard {
+    pub entries: Vec<HighScore>,
+}
+
+impl HighScoreBoard {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        storage::load_or_default(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        storage::save(self, path)
+    }
+
+    // Returns the entry's 1-based rank, or None if it didn't make the board
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize> {
+        let pos = self.entries.iter().position(|e| e.score < entry.score).unwrap_or(self.entries.len());
+        if pos >= MAX_ENTRIES {
+            return None;
+        }
+        self.entries.insert(pos, entry);
+        self.entries.truncate(MAX_ENTRIES);
+        Some(pos + 1)
+    }
+
+    // Unverifiable entries are marked with a '?'
+    pub fn lines(&self) -> Vec<String> {
+        self.entries.iter().enumerate()
+            .map(|(i, e)| format!("{:>2}. {:>6}{}", i + 1, e.score, if e.is_verified() { "" } else { " ?" }))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn untouched_entry_verifies() {
+        let entry = HighScore::new(120, 7, "wwdm".to_string());
+        assert!(entry.is_verified());
+    }
+
+    #[test]
+    fn edited_entry_fails_verification() {
+        let entry = HighScore::new(120, 7, "wwdm".to_string());
+        assert!(!HighScore { score: 9000, ..entry.clone() }.is_verified());
+        assert!(!HighScore { seed: 8, ..entry.clone() }.is_verified());
+        assert!(!HighScore { inputs: "wwdmm".to_string(), ..entry }.is_verified());
+    }
+
+    #[test]
+    fn token_is_stable() {
+        // Changing the hash would flag every board already on disk
+        assert_eq!(fnv1a(&[b"a"]), 0xaf63dc4c8601ec8c);
+        assert_eq!(verification_token(120, 7, "wwdm"), "514d4ab4c05179ed");
+    }
+
+    #[test]
+    fn board_keeps_the_top_scores_in_order() {
+        let mut board = HighScoreBoard::default();
+        for score in 1..=MAX_ENTRIES as u32 {
+            board.insert(HighScore::new(score * 10, 1, String::new()));
+        }
+        assert_eq!(board.insert(HighScore::new(5, 1, String::new())), None);
+        assert_eq!(board.insert(HighScore::new(55, 1, String::new())), Some(6));
+        assert_eq!(board.entries.len(), MAX_ENTRIES);
+        assert_eq!(board.entries[0].score, MAX_ENTRIES as u32 * 10);
+        assert!(board.entries.windows(2).all(|w| w[0].score >= w[1].score));
+    }
+
+    #[test]
+    fn unverified_entries_are_flagged() {
+        let mut board = HighScoreBoard::default();
+        board.insert(HighScore::new(50, 1, String::new()));
+        let mut forged = HighScore::new(40, 1, String::new());
+        forged.score = 45;
+        board.insert(forged);
+        assert_eq!(board.lines(), vec![" 1.     50".to_string(), " 2.     45 ?".to_string()]);
+    }
+}
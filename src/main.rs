@@ -1,15 +1,56 @@
-use std::collections::HashMap;
-use std::io::{stdout, Write};
-use std::time::Duration;
+mod arena;
+mod attract;
+mod autopilot;
+mod config;
+mod difficulty;
+mod drone;
+mod geometry;
+mod ghost;
+mod highscores;
+mod render;
+mod save;
+mod scoring;
+mod selftest;
+mod sound;
+mod spectate;
+mod stats;
+mod storage;
+mod theme;
+mod trial;
+mod upgrades;
+
+use std::collections::{HashMap, HashSet};
+use std::io::stdout;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 use crossterm::{
     cursor,
-    event::{self, Event, KeyCode},
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
     execute,
-    terminal::{self, ClearType},
-    style::{Color, Print, SetForegroundColor, ResetColor},
+    terminal,
 };
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
 use serde::{Serialize, Deserialize};
 use tokio::task::yield_now;
+use arena::Arena;
+use attract::{Attract, AttractScreen};
+use autopilot::{autopilot, autopilot_mode, AutopilotMode};
+use config::{LevelType, MilestoneEvent, Settings, SpawnPattern, WinCondition, SETTINGS_FILE};
+use difficulty::{resolve_difficulty, CollisionMode, DifficultyParams};
+use drone::{drone_system, Drone};
+use geometry::{clamp_point, rect_overlap, wrap_point};
+use ghost::{Ghost, GHOST_FILE};
+use highscores::{HighScore, HighScoreBoard, HIGHSCORES_FILE};
+use save::{slot_path, SaveSlot, SavedRun};
+use scoring::score_for_mine;
+use sound::{Cue, Sound};
+use spectate::Broadcaster;
+use stats::{record_history, RunStats, RunSummary};
+use trial::{checkpoint_gates, BestSplits, TimeTrial, BEST_SPLITS_FILE};
+use upgrades::{Effect, UpgradeDef};
+use render::{Renderer, FIELD_HEIGHT, FIELD_WIDTH};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 enum Resource {
@@ -18,20 +59,55 @@ enum Resource {
     Gold,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-enum Upgrade {
-    Laser,
-    Shields,
-    Thrusters,
+impl Resource {
+    // Ticks of mining needed to extract one node without upgrades
+    fn hardness(self) -> u32 {
+        match self {
+            Resource::Iron => 1,
+            Resource::Crystal => 2,
+            Resource::Gold => 4,
+        }
+    }
 }
 
+const MAX_FUEL: f32 = 100.0;
+const MAX_HULL: f32 = 100.0;
+// Width of the ship glyph in cells
+const SHIP_WIDTH: u16 = 3;
+
+// Tank sizes, fixed for the run
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct FuelTanks {
+    main: f32,
+    // Emergency tank, only burnt once the main tank is dry; 0 means none
+    reserve: f32,
+    // Fraction of overflowing refuel that reaches the reserve
+    reserve_refill_rate: f32,
+}
+
+// Player two starts this many cells to the right of player one
+const ALLY_OFFSET: u16 = 5;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct Ship {
     fuel: f32,
+    #[serde(default)]
+    reserve_fuel: f32,
+    tanks: FuelTanks,
+    hull: f32,
     cargo: HashMap<Resource, u32>,
-    upgrades: Vec<Upgrade>,
+    upgrades: Vec<UpgradeDef>,
     x: u16,
     y: u16,
+    // Consecutive ticks spent mining the node under the ship
+    #[serde(default)]
+    mine_progress: u32,
+    // Units the hold takes, the drone's hauls included; None is unlimited
+    #[serde(default)]
+    cargo_capacity: Option<u32>,
+    // Asteroid strikes the shields can still absorb
+    #[serde(default)]
+    shield_charges: u32,
 }
 
 impl Ship {
@@ -41,16 +117,82 @@ impl Ship {
         cargo.insert(Resource::Crystal, 0);
         cargo.insert(Resource::Gold, 0);
         Ship {
-            fuel: 100.0,
+            fuel: MAX_FUEL,
+            reserve_fuel: 0.0,
+            tanks: FuelTanks { main: MAX_FUEL, reserve: 0.0, reserve_refill_rate: 0.5 },
+            hull: MAX_HULL,
             cargo,
             upgrades: Vec::new(),
             x: 10,
             y: 10,
+            mine_progress: 0,
+            cargo_capacity: None,
+            shield_charges: 0,
         }
     }
+
+    // Main tank capacity
+    fn max_fuel(&self) -> f32 {
+        self.tanks.main + upgrades::fuel_capacity_bonus(&self.upgrades)
+    }
+
+    // Units the hold can still take
+    fn cargo_room(&self) -> u32 {
+        let held: u32 = self.cargo.values().sum();
+        self.cargo_capacity.map_or(u32::MAX, |cap| cap.saturating_sub(held))
+    }
+
+    fn total_fuel(&self) -> f32 {
+        self.fuel + self.reserve_fuel
+    }
+
+    // Drains the main tank, then the reserve
+    fn burn_fuel(&mut self, amount: f32) {
+        let from_main = amount.min(self.fuel);
+        self.fuel -= from_main;
+        self.reserve_fuel = (self.reserve_fuel - (amount - from_main)).max(0.0);
+    }
+
+    // Fills the main tank first; what overflows tops up the reserve at its
+    // slower rate and anything beyond that is lost. Returns the fuel gained.
+    fn refuel(&mut self, amount: f32) -> f32 {
+        let to_main = amount.min(self.max_fuel() - self.fuel).max(0.0);
+        self.fuel += to_main;
+        let to_reserve = ((amount - to_main) * self.tanks.reserve_refill_rate)
+            .min(self.tanks.reserve - self.reserve_fuel)
+            .max(0.0);
+        self.reserve_fuel += to_reserve;
+        to_main + to_reserve
+    }
+
+    // Cells moved per step
+    fn speed(&self) -> u16 {
+        1 + upgrades::move_speed_bonus(&self.upgrades)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FuelState {
+    Good,
+    Low,
+    Critical,
+}
+
+// Fuel at or below which the tank reads as low, unless the reserve is set higher
+const LOW_FUEL: f32 = 50.0;
+
+// `reserve` is the level warnings kick in at
+fn fuel_state(fuel: f32, reserve: f32) -> FuelState {
+    if fuel <= reserve {
+        FuelState::Critical
+    } else if fuel <= LOW_FUEL.max(reserve) {
+        FuelState::Low
+    } else {
+        FuelState::Good
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 struct Rect {
     x: u16,
     y: u16,
@@ -65,214 +207,3120 @@ fn check_collision(ship: &Rect, entity: &Rect) -> bool {
     ship.y + ship.h > entity.y
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+enum GameMode {
+    Welcome,
+    Playing,
+    GameOver,
+    // The run's win condition was met
+    Victory,
+    // The run hit its tick or time cap
+    TimeUp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum DeathCause {
+    Asteroid,
+    OutOfFuel,
+    HullBreached,
+}
+
+fn initial_mode(settings: &Settings) -> GameMode {
+    if settings.skip_intro { GameMode::Playing } else { GameMode::Welcome }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum InputEvent {
     Up,
     Down,
     Left,
     Right,
+    // Two movement keys pressed in the same frame
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
     Mine,
+    // Two-player controls for the ally ship
+    AllyUp,
+    AllyDown,
+    AllyLeft,
+    AllyRight,
+    AllyMine,
+    // Share fuel with the adjacent other ship: player to ally, or ally to player
+    Tether,
+    AllyTether,
+    // Show/hide the stats overlay; doesn't advance the game
+    ToggleStats,
+    // Hide/show the HUD, giving its rows to the playfield
+    ToggleHud,
+    // Switch the HUD's cargo readout between counts and credit value
+    ToggleCargoReadout,
+    // Trade cargo for fuel while docked at the station
+    Convert,
+    // Let the autopilot fly whenever no key is pressed
+    ToggleAutopilot,
+    // Freeze the game; doesn't advance it
+    Pause,
     Quit,
+    // Ctrl-C: leaves straight away, without asking
+    Interrupt,
     None,
 }
 
-impl From<crossterm::event::KeyEvent> for InputEvent {
-    fn from(key: crossterm::event::KeyEvent) -> Self {
+// Set by the SIGINT handler; raw mode swallows Ctrl-C keypresses, so this only
+// fires for signals sent from outside the terminal (e.g. `kill -INT`)
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+fn is_ctrl_c(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
+
+impl InputEvent {
+    // Compact one-character encoding used to record runs. Keys that type a
+    // visible character log that character; the rest get arbitrary ones.
+    fn code(&self) -> char {
+        match self {
+            InputEvent::Up => 'w',
+            InputEvent::Down => 's',
+            InputEvent::Left => 'a',
+            InputEvent::Right => 'd',
+            // Laid out like a numeric keypad
+            InputEvent::UpLeft => '7',
+            InputEvent::UpRight => '9',
+            InputEvent::DownLeft => '1',
+            InputEvent::DownRight => '3',
+            InputEvent::Mine => 'm',
+            InputEvent::AllyUp => 'i',
+            InputEvent::AllyDown => 'k',
+            InputEvent::AllyLeft => 'j',
+            InputEvent::AllyRight => 'l',
+            InputEvent::AllyMine => 'n',
+            InputEvent::Tether => 't',
+            InputEvent::AllyTether => '/',
+            InputEvent::Convert => 'c',
+            InputEvent::ToggleStats => 'u',
+            InputEvent::ToggleHud => 'h',
+            InputEvent::ToggleCargoReadout => 'v',
+            InputEvent::ToggleAutopilot => 'p',
+            InputEvent::Pause => 'x',
+            InputEvent::Quit => 'q',
+            InputEvent::Interrupt => '!',
+            InputEvent::None => '.',
+        }
+    }
+
+    fn is_movement(&self) -> bool {
+        self.delta() != (0, 0) || self.ally_action().delta() != (0, 0)
+    }
+
+    // Cell offset a movement input moves by
+    fn delta(&self) -> (i32, i32) {
+        match self {
+            InputEvent::Up => (0, -1),
+            InputEvent::Down => (0, 1),
+            InputEvent::Left => (-1, 0),
+            InputEvent::Right => (1, 0),
+            InputEvent::UpLeft => (-1, -1),
+            InputEvent::UpRight => (1, -1),
+            InputEvent::DownLeft => (-1, 1),
+            InputEvent::DownRight => (1, 1),
+            _ => (0, 0),
+        }
+    }
+
+    // The movement input for a cell offset, each axis clamped to one cell
+    fn from_delta((dx, dy): (i32, i32)) -> InputEvent {
+        match (dx.signum(), dy.signum()) {
+            (0, -1) => InputEvent::Up,
+            (0, 1) => InputEvent::Down,
+            (-1, 0) => InputEvent::Left,
+            (1, 0) => InputEvent::Right,
+            (-1, -1) => InputEvent::UpLeft,
+            (1, -1) => InputEvent::UpRight,
+            (-1, 1) => InputEvent::DownLeft,
+            (1, 1) => InputEvent::DownRight,
+            _ => InputEvent::None,
+        }
+    }
+
+    // The ally's controls translated to the events the player's systems understand
+    fn ally_action(&self) -> InputEvent {
+        match self {
+            InputEvent::AllyUp => InputEvent::Up,
+            InputEvent::AllyDown => InputEvent::Down,
+            InputEvent::AllyLeft => InputEvent::Left,
+            InputEvent::AllyRight => InputEvent::Right,
+            InputEvent::AllyMine => InputEvent::Mine,
+            _ => InputEvent::None,
+        }
+    }
+}
+
+impl From<KeyEvent> for InputEvent {
+    fn from(key: KeyEvent) -> Self {
+        // Ctrl-C takes the clean shutdown path, skipping the quit prompt
+        if is_ctrl_c(&key) {
+            return InputEvent::Interrupt;
+        }
         match key.code {
             KeyCode::Char('w') => InputEvent::Up,
             KeyCode::Char('a') => InputEvent::Left,
             KeyCode::Char('s') => InputEvent::Down,
             KeyCode::Char('d') => InputEvent::Right,
             KeyCode::Char(' ') => InputEvent::Mine,
+            KeyCode::Char('t') => InputEvent::Tether,
+            KeyCode::Char('c') => InputEvent::Convert,
+            KeyCode::Up => InputEvent::AllyUp,
+            KeyCode::Down => InputEvent::AllyDown,
+            KeyCode::Left => InputEvent::AllyLeft,
+            KeyCode::Right => InputEvent::AllyRight,
+            KeyCode::Enter => InputEvent::AllyMine,
+            KeyCode::Char('/') => InputEvent::AllyTether,
+            KeyCode::Tab => InputEvent::ToggleStats,
+            KeyCode::Char('h') => InputEvent::ToggleHud,
+            KeyCode::Char('v') => InputEvent::ToggleCargoReadout,
+            KeyCode::Char('p') => InputEvent::ToggleAutopilot,
+            KeyCode::Esc => InputEvent::Pause,
             KeyCode::Char('q') => InputEvent::Quit,
             _ => InputEvent::None,
         }
     }
 }
 
-async fn read_input() -> InputEvent {
+// Waits for a key, or gives up with `InputEvent::None` once `timeout` passes
+async fn read_input(timeout: Option<Duration>) -> InputEvent {
+    let deadline = timeout.map(|t| Instant::now() + t);
     loop {
+        if INTERRUPTED.load(Ordering::SeqCst) {
+            return InputEvent::Interrupt;
+        }
         if event::poll(Duration::from_millis(10)).unwrap() {
             if let Event::Key(key) = event::read().unwrap() {
                 return InputEvent::from(key);
             }
         }
+        if deadline.is_some_and(|d| Instant::now() >= d) {
+            return InputEvent::None;
+        }
         yield_now().await;
     }
 }
 
+// Reads a frame's worth of input: the first key, as `read_input`, then
+// whatever else is already queued behind it
+async fn read_inputs(timeout: Option<Duration>) -> Vec<InputEvent> {
+    let mut inputs = vec![read_input(timeout).await];
+    while event::poll(Duration::ZERO).unwrap() {
+        if let Event::Key(key) = event::read().unwrap() {
+            inputs.push(InputEvent::from(key));
+        }
+    }
+    inputs
+}
+
+// What a frame's batch of inputs comes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FrameAction {
+    // Player movement summed over the batch, each axis at most one cell
+    movement: (i32, i32),
+    // Player two's first move or mine, kept apart so it doesn't cost player
+    // one theirs
+    ally: Option<InputEvent>,
+    // The first input that isn't movement or mining by either player;
+    // Interrupt, then Quit, beats everything
+    command: Option<InputEvent>,
+}
+
+impl FrameAction {
+    // The single input the frame acts on. A command wins over movement, which
+    // is dropped rather than carried into the next frame.
+    fn input(&self) -> InputEvent {
+        self.command.unwrap_or_else(|| InputEvent::from_delta(self.movement))
+    }
+
+    fn ally_input(&self) -> InputEvent {
+        self.ally.unwrap_or(InputEvent::None)
+    }
+}
+
+// Folds a batch into one action: held-key repeats collapse into a single
+// press, and two directions pressed together make a diagonal
+fn coalesce_inputs(inputs: &[InputEvent]) -> FrameAction {
+    let mut moves: Vec<(i32, i32)> = Vec::new();
+    let mut ally = None;
+    let mut command = None;
+    for input in inputs {
+        match input.delta() {
+            (0, 0) if *input == InputEvent::Interrupt => command = Some(InputEvent::Interrupt),
+            (0, 0) if *input == InputEvent::Quit && command != Some(InputEvent::Interrupt) => command = Some(InputEvent::Quit),
+            (0, 0) if *input == InputEvent::None => {}
+            (0, 0) if input.ally_action() != InputEvent::None => ally = ally.or(Some(*input)),
+            (0, 0) => command = command.or(Some(*input)),
+            delta if !moves.contains(&delta) => moves.push(delta),
+            _ => {}
+        }
+    }
+    let (dx, dy) = moves.iter().fold((0, 0), |(x, y), (dx, dy)| (x + dx, y + dy));
+    FrameAction { movement: (dx.clamp(-1, 1), dy.clamp(-1, 1)), ally, command }
+}
+
+// Movement keys still buffered from mining would otherwise carry the ship
+// straight off the node, so they're ignored for `window` after each mine
+struct MineDeadTime {
+    window: Duration,
+    last_mine: Option<Instant>,
+}
+
+impl MineDeadTime {
+    fn new(window: Duration) -> Self {
+        MineDeadTime { window, last_mine: None }
+    }
+
+    fn mined(&mut self, at: Instant) {
+        self.last_mine = Some(at);
+    }
+
+    // Whether either player's move at `now` falls inside the dead time
+    fn suppresses(&self, input: &InputEvent, ally: &InputEvent, now: Instant) -> bool {
+        (input.is_movement() || ally.is_movement())
+            && self.last_mine.is_some_and(|t| now.saturating_duration_since(t) < self.window)
+    }
+}
+
+// --- Terminal ---
+// Owns raw mode and the alternate screen; restores the terminal when dropped,
+// so quitting, Ctrl-C and panics all leave the shell usable
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn new() -> Self {
+        terminal::enable_raw_mode().unwrap();
+        execute!(stdout(), terminal::EnterAlternateScreen, cursor::Hide).unwrap();
+        TerminalGuard
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
 // --- Basic Entities for Asteroids and Resources ---
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct Asteroid {
     x: u16,
     y: u16,
+    // Cells moved per tick; field asteroids are stationary
+    vx: i16,
+    vy: i16,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct ResourceNode {
     x: u16,
     y: u16,
     kind: Resource,
+    // Game time the node appeared, for expiry
+    spawned_at: Duration,
+    // Fraction of a cell the magnet has pulled the node so far
+    drift: (f32, f32),
+    // Extractions left before the node is used up, out of `richness`
+    amount: u32,
+    richness: u32,
+    // Mining it costs fuel; only a Scanner shows it clearly
+    #[serde(default)]
+    contaminated: bool,
 }
 
-// --- Rendering ---
-fn render(ship: &Ship, asteroids: &[Asteroid], resources: &[ResourceNode], score: u32) {
-    let mut stdout = stdout();
-    execute!(stdout, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0)).unwrap();
-
-    // Draw border
-    println!("╔════════════════════════════════════╗");
-    for y in 0..15 {
-        print!("║");
-        for x in 0..34 {
-            // Draw ship
-            if x == ship.x && y == ship.y {
-                print!(">A<");
-                // Skip next 2 chars for ship width
-                for _ in 0..2 { if x < 32 { print!(" "); } }
-            }
-            // Draw asteroids
-            else if asteroids.iter().any(|a| a.x == x && a.y == y) {
-                print!("O");
-            }
-            // Draw resources
-            else if let Some(res) = resources.iter().find(|r| r.x == x && r.y == y) {
-                match res.kind {
-                    Resource::Iron => print!("*"),
-                    Resource::Crystal => print!("♦"),
-                    Resource::Gold => print!("$"),
-                }
-            }
-            else {
-                print!(" ");
-            }
-        }
-        println!("║");
+impl ResourceNode {
+    fn new(x: u16, y: u16, kind: Resource, spawned_at: Duration) -> Self {
+        ResourceNode { x, y, kind, spawned_at, drift: (0.0, 0.0), amount: 1, richness: 1, contaminated: false }
+    }
+
+    // A node that can be mined `richness` times over
+    fn rich(self, richness: u32) -> Self {
+        ResourceNode { amount: richness, richness, ..self }
     }
-    println!("╠════════════════════════════════════╣");
-    print!("║ FUEL: ");
-    let fuel_blocks = (ship.fuel / 10.0).round() as usize;
-    for _ in 0..fuel_blocks { print!("█"); }
-    for _ in fuel_blocks..10 { print!("░"); }
-    print!("  CARGO: {}   SCORE: {} ║", ship.cargo.values().sum::<u32>(), score);
-    println!();
-    println!("╚════════════════════════════════════╝");
-    stdout.flush().unwrap();
 }
 
 // --- Physics & Game Logic ---
-fn physics_system(input: &InputEvent, ship: &mut Ship) {
-    match input {
-        InputEvent::Up if ship.y > 0 => ship.y -= 1,
-        InputEvent::Down if ship.y < 14 => ship.y += 1,
-        InputEvent::Left if ship.x > 0 => ship.x -= 1,
-        InputEvent::Right if ship.x < 31 => ship.x += 1,
-        _ => {}
+// Cells the ship's glyph covers with its left end at (x, y)
+fn glyph_cells(x: u16, y: u16) -> impl Iterator<Item = (u16, u16)> {
+    (x..x + SHIP_WIDTH).map(move |x| (x, y))
+}
+
+// Walls just stop the ship; flying into one is harmless
+fn physics_system(input: &InputEvent, ship: &mut Ship, bounds: &Rect, arena: &Arena, fuel_drain: f32) {
+    let (dx, dy) = input.delta();
+    // One cell at a time, so fast ships still stop at walls
+    for _ in 0..ship.speed() {
+        let (x, y) = clamp_point(ship.x as i32 + dx, ship.y as i32 + dy, bounds);
+        if glyph_cells(x, y).any(|(x, y)| arena.is_wall(x, y)) {
+            break;
+        }
+        (ship.x, ship.y) = (x, y);
     }
     // Fuel depletes over time
-    ship.fuel = (ship.fuel - 0.5).max(0.0);
+    ship.burn_fuel(fuel_drain);
+}
+
+// Moves asteroids by their velocity, wrapping around the edges of `bounds`
+fn movement_system(asteroids: &mut [Asteroid], bounds: &Rect) {
+    for a in asteroids.iter_mut() {
+        (a.x, a.y) = wrap_point(a.x as i32 + a.vx as i32, a.y as i32 + a.vy as i32, bounds);
+    }
+}
+
+// --- Co-op Tether ---
+// Fuel units per second pumped between tethered ships
+const TETHER_RATE: f32 = 20.0;
+
+// Ships are adjacent when their glyphs touch or sit diagonally next to each other
+fn ships_adjacent(a: &Ship, b: &Ship) -> bool {
+    a.x.abs_diff(b.x) <= SHIP_WIDTH && a.y.abs_diff(b.y) <= 1
+}
+
+// Moves fuel from donor to recipient, limited by what the donor has left and
+// the room in the recipient's tank. Only main tanks are shared; reserves stay
+// with their ship. Returns the amount moved.
+fn transfer_fuel(from: &mut Ship, to: &mut Ship, dt: Duration) -> f32 {
+    let amount = (TETHER_RATE * dt.as_secs_f32())
+        .min(from.fuel)
+        .min(to.max_fuel() - to.fuel)
+        .max(0.0);
+    from.fuel -= amount;
+    to.fuel += amount;
+    amount
+}
+
+// Moves everything one row down the screen, dropping whatever falls off the
+// bottom. Frozen asteroids, as during resource rain, stay where they are.
+fn scroll_system(asteroids: &mut Vec<Asteroid>, resources: &mut Vec<ResourceNode>, bounds: &Rect, freeze_asteroids: bool) {
+    let bottom = bounds.y + bounds.h;
+    if !freeze_asteroids {
+        for a in asteroids.iter_mut() {
+            a.y += 1;
+        }
+        asteroids.retain(|a| a.y < bottom);
+    }
+    for r in resources.iter_mut() {
+        r.y += 1;
+    }
+    resources.retain(|r| r.y < bottom);
+}
+
+// Removes resource nodes older than `lifetime`; a zero lifetime never expires
+fn expiry_system(resources: &mut Vec<ResourceNode>, now: Duration, lifetime: Duration) {
+    if lifetime.is_zero() {
+        return;
+    }
+    resources.retain(|r| now.saturating_sub(r.spawned_at) < lifetime);
+}
+
+// Fraction of the cells in `bounds` occupied by at least one asteroid
+fn field_density(asteroids: &[Asteroid], bounds: &Rect) -> f32 {
+    let occupied: HashSet<(u16, u16)> = asteroids.iter()
+        .filter(|a| rect_overlap(&Rect { x: a.x, y: a.y, w: 1, h: 1 }, bounds))
+        .map(|a| (a.x, a.y))
+        .collect();
+    let area = bounds.w as f32 * bounds.h as f32;
+    if area == 0.0 { 0.0 } else { occupied.len() as f32 / area }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Collision {
+    None,
+    // Number of asteroids whose outer ring the ship is clipping
+    Graze(u32),
+    // Index of the asteroid hit
+    Core(usize),
+}
+
+// An asteroid's instant-kill core and the graze ring `graze_radius` cells around it
+fn asteroid_zones(a: &Asteroid, graze_radius: u16) -> (Rect, Rect) {
+    let core = Rect { x: a.x, y: a.y, w: 1, h: 1 };
+    let x = a.x.saturating_sub(graze_radius);
+    let y = a.y.saturating_sub(graze_radius);
+    let graze = Rect { x, y, w: a.x + graze_radius + 1 - x, h: a.y + graze_radius + 1 - y };
+    (core, graze)
+}
+
+// The whole glyph can be hit, so what's drawn is what collides
+fn ship_hitbox(ship: &Ship) -> Rect {
+    Rect { x: ship.x, y: ship.y, w: SHIP_WIDTH, h: 1 }
+}
+
+fn collision_system(ship: &Ship, asteroids: &[Asteroid], graze_radius: u16) -> Collision {
+    let ship_rect = ship_hitbox(ship);
+    let mut grazes = 0;
+    for (i, a) in asteroids.iter().enumerate() {
+        let (core, graze) = asteroid_zones(a, graze_radius);
+        if check_collision(&ship_rect, &core) {
+            return Collision::Core(i);
+        }
+        if graze_radius > 0 && check_collision(&ship_rect, &graze) {
+            grazes += 1;
+        }
+    }
+    if grazes > 0 { Collision::Graze(grazes) } else { Collision::None }
+}
+
+// --- Knockback ---
+// Hull lost to an asteroid strike when knockback is on
+const KNOCKBACK_DAMAGE: f32 = 25.0;
+
+// Which way a struck ship is thrown: along the asteroid's path if it's moving,
+// otherwise back the way the ship came, otherwise down
+fn knockback_direction(asteroid: &Asteroid, ship_move: (i32, i32)) -> (i32, i32) {
+    let carried = (asteroid.vx.signum() as i32, asteroid.vy.signum() as i32);
+    if carried != (0, 0) {
+        carried
+    } else if ship_move != (0, 0) {
+        (-ship_move.0, -ship_move.1)
+    } else {
+        (0, 1)
+    }
+}
+
+// Pushes the ship up to `distance` cells, stopping short of the edge of
+// `bounds`, walls and anywhere its glyph would land on an asteroid. Returns
+// the cells actually moved.
+fn knockback(ship: &mut Ship, (dx, dy): (i32, i32), distance: u16, bounds: &Rect, arena: &Arena, asteroids: &[Asteroid]) -> u16 {
+    for moved in 0..distance {
+        let (x, y) = clamp_point(ship.x as i32 + dx, ship.y as i32 + dy, bounds);
+        let blocked = (x, y) == (ship.x, ship.y)
+            || glyph_cells(x, y).any(|(x, y)| arena.is_wall(x, y))
+            || asteroids.iter().any(|a| a.y == y && (x..x + SHIP_WIDTH).contains(&a.x));
+        if blocked {
+            return moved;
+        }
+        (ship.x, ship.y) = (x, y);
+    }
+    distance
+}
+
+// Ticks of mining a resource takes; a mining laser halves it (rounding up)
+fn mine_duration(kind: Resource, upgrades: &[UpgradeDef]) -> u32 {
+    let hardness = kind.hardness();
+    if upgrades::has_effect(upgrades, &Effect::Laser) {
+        hardness.div_ceil(2)
+    } else {
+        hardness
+    }
+}
+
+// --- Station ---
+// Where the station sits; ships docked on it can trade cargo for fuel
+const STATION: (u16, u16) = (28, 2);
+
+// An arena walling in where a ship starts, or over the station, can't be played
+fn check_arena(arena: &Arena) -> Result<(), String> {
+    let start = Ship::new();
+    let ship_cells = [start.x, start.x + ALLY_OFFSET].into_iter()
+        .flat_map(|x| glyph_cells(x, start.y));
+    for (x, y) in ship_cells.chain(std::iter::once(STATION)) {
+        if arena.is_wall(x, y) {
+            return Err(format!("arena has a wall at ({}, {}), where a ship starts or the station stands", x, y));
+        }
+    }
+    Ok(())
+}
+
+// Burns `amount` of a resource from the cargo hold for `rate` fuel each,
+// returning the fuel gained. Whatever doesn't fit in the tank is lost.
+fn convert_to_fuel(ship: &mut Ship, resource: Resource, amount: u32, rate: f32) -> Result<f32, String> {
+    let held = ship.cargo.get(&resource).copied().unwrap_or(0);
+    if held < amount {
+        return Err(format!("only {} {:?} in the hold", held, resource));
+    }
+    ship.cargo.insert(resource, held - amount);
+    Ok(ship.refuel(amount as f32 * rate))
+}
+
+// Credits the whole hold would sell for
+fn cargo_value(ship: &Ship, values: &HashMap<Resource, u32>) -> u32 {
+    ship.cargo.iter().map(|(kind, &count)| count * values.get(kind).copied().unwrap_or(0)).sum()
+}
+
+// Stows a mined node's resources in the ship's cargo, as far as the hold has
+// room, along with any fuel that kind of node gives
+fn deliver(ship: &mut Ship, kind: Resource, amount: u32, fuel: f32) {
+    let amount = amount.min(ship.cargo_room());
+    *ship.cargo.entry(kind).or_insert(0) += amount;
+    ship.refuel(fuel);
 }
 
-fn collision_system(ship: &Ship, asteroids: &[Asteroid]) -> bool {
-    asteroids.iter().any(|a| a.x == ship.x && a.y == ship.y)
+// Units a node yields: one, give or take up to `variance`, but never nothing
+fn mining_yield(rng: &mut StdRng, variance: u32) -> u32 {
+    if variance == 0 {
+        return 1;
+    }
+    let v = variance as i32;
+    (1 + rng.gen_range(-v..=v)).max(1) as u32
 }
 
-fn mining_system(input: &InputEvent, ship: &mut Ship, resources: &mut Vec<ResourceNode>) -> Option<Resource> {
-    if let InputEvent::Mine = input {
+// Mining has to be held on a node for its whole duration; any other input
+// (or drifting off the node) starts it over. With `auto_contact`, sitting on a
+// node counts as mining it, no key needed (`Settings::auto_mine`).
+fn mining_system(
+    input: &InputEvent,
+    ship: &mut Ship,
+    resources: &mut Vec<ResourceNode>,
+    settings: &Settings,
+    fuel_scale: f32,
+    rng: &mut StdRng,
+) -> Option<Resource> {
+    // A full hold leaves nodes where they are
+    if (settings.auto_mine || *input == InputEvent::Mine) && ship.cargo_room() > 0 {
         if let Some(idx) = resources.iter().position(|r| r.x == ship.x && r.y == ship.y) {
-            let res = resources.remove(idx);
-            *ship.cargo.entry(res.kind).or_insert(0) += 1;
-            // Refuel if crystal
-            if res.kind == Resource::Crystal {
-                ship.fuel = (ship.fuel + 20.0).min(100.0);
+            // On the scrolling level a node only passes under the ship for a
+            // single tick, so everything comes out in one go
+            let needed = match settings.level {
+                LevelType::Scrolling => 1,
+                _ => mine_duration(resources[idx].kind, &ship.upgrades),
+            };
+            ship.mine_progress += 1;
+            if ship.mine_progress < needed {
+                return None;
+            }
+            ship.mine_progress = 0;
+            let kind = resources[idx].kind;
+            let contaminated = resources[idx].contaminated;
+            resources[idx].amount -= 1;
+            if resources[idx].amount == 0 {
+                resources.remove(idx);
             }
-            return Some(res.kind);
+            deliver(ship, kind, mining_yield(rng, settings.yield_variance), settings.fuel_value(kind, fuel_scale));
+            if contaminated {
+                ship.burn_fuel(CONTAMINATION_FUEL_LOSS);
+            }
+            return Some(kind);
         }
     }
+    ship.mine_progress = 0;
     None
 }
 
-#[tokio::main]
-async fn main() {
-    // Setup terminal
-    let mut stdout = stdout();
-    terminal::enable_raw_mode().unwrap();
-    execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide).unwrap();
-
-    let mut ship = Ship::new();
-    let mut asteroids = vec![
-        Asteroid { x: 5, y: 5 },
-        Asteroid { x: 20, y: 8 },
-        Asteroid { x: 15, y: 12 },
-    ];
+// Fuel a contaminated node costs the ship that mines it, after any fuel it gave
+const CONTAMINATION_FUEL_LOSS: f32 = 15.0;
+
+// --- Magnet ---
+// Nodes further away than this are out of the magnet's reach
+const MAGNET_RADIUS: f32 = 6.0;
+
+// Cells per tick a node drifts towards the ship. The pull falls off with
+// distance, so close nodes snap in while distant ones creep
+fn magnet_pull(ship: &Ship, node: &ResourceNode, strength: f32) -> (f32, f32) {
+    let dx = ship.x as f32 - node.x as f32;
+    let dy = ship.y as f32 - node.y as f32;
+    let dist = (dx * dx + dy * dy).sqrt();
+    if dist == 0.0 || dist > MAGNET_RADIUS {
+        return (0.0, 0.0);
+    }
+    let speed = (strength / dist).min(1.0);
+    (dx / dist * speed, dy / dist * speed)
+}
+
+fn magnet_system(ship: &Ship, resources: &mut [ResourceNode], asteroids: &[Asteroid], arena: &Arena, strength: f32) {
+    if !upgrades::has_effect(&ship.upgrades, &Effect::Magnet) {
+        return;
+    }
+    for i in 0..resources.len() {
+        let (px, py) = magnet_pull(ship, &resources[i], strength);
+        let node = &mut resources[i];
+        // Drift left over from before the node lined up with the ship is dropped
+        node.drift.0 = if px == 0.0 { 0.0 } else { node.drift.0 + px };
+        node.drift.1 = if py == 0.0 { 0.0 } else { node.drift.1 + py };
+        let step_x = node.drift.0.trunc() as i32;
+        let step_y = node.drift.1.trunc() as i32;
+        if step_x == 0 && step_y == 0 {
+            continue;
+        }
+        let x = (node.x as i32 + step_x) as u16;
+        let y = (node.y as i32 + step_y) as u16;
+        // Nodes never pile up on the same cell or get dragged into walls or asteroids
+        if arena.is_wall(x, y)
+            || resources.iter().any(|r| (r.x, r.y) == (x, y))
+            || asteroids.iter().any(|a| (a.x, a.y) == (x, y))
+        {
+            continue;
+        }
+        let node = &mut resources[i];
+        node.drift.0 -= step_x as f32;
+        node.drift.1 -= step_y as f32;
+        node.x = x;
+        node.y = y;
+    }
+}
+
+// --- Camera ---
+// Scrolls the view (`cam`) over a world bigger than the screen. The ship
+// moves freely inside a `deadzone` (width, height) box in the middle of the
+// view; pushing past its edge drags the view along, up to the world's edges.
+fn camera_follow(cam: Rect, ship: &Ship, deadzone: (u16, u16), world: &Rect) -> Rect {
+    let axis = |start: u16, size: u16, pos: u16, dead: u16, world_start: u16, world_size: u16| {
+        let dead = dead.min(size) as i32;
+        let near = start as i32 + (size as i32 - dead) / 2;
+        let far = near + dead - 1;
+        let pos = pos as i32;
+        let mut start = start as i32;
+        if pos < near {
+            start -= near - pos;
+        } else if pos > far {
+            start += pos - far;
+        }
+        let last = (world_start as i32 + world_size as i32 - size as i32).max(world_start as i32);
+        start.clamp(world_start as i32, last) as u16
+    };
+    Rect {
+        x: axis(cam.x, cam.w, ship.x, deadzone.0, world.x, world.w),
+        y: axis(cam.y, cam.h, ship.y, deadzone.1, world.y, world.h),
+        ..cam
+    }
+}
+
+// Area asteroids and resources spawn in, with the HUD showing
+const PLAYFIELD: Rect = Rect { x: 0, y: 0, w: 32, h: 15 };
+// Separator plus one status line per ship
+const HUD_ROWS: u16 = 2;
+
+// Pause between frames
+const FRAME_DELAY: Duration = Duration::from_millis(80);
+// Perf mode redraws at most this often; the game itself runs at full speed
+const PERF_REDRAW_INTERVAL: Duration = Duration::from_millis(160);
+// How long the autopilot waits for a key before flying itself
+const AUTOPILOT_FRAME: Duration = Duration::from_millis(120);
+// How often the ship advances by itself in one-button mode
+const ONE_BUTTON_FRAME: Duration = Duration::from_millis(200);
+// Redraw interval while paused, so animations keep playing
+const PAUSED_FRAME: Duration = Duration::from_millis(120);
+
+// --- Welcome Preview ---
+// Attract animation behind the title: asteroids slowly drifting across the field
+const PREVIEW_FRAME: Duration = Duration::from_millis(250);
+
+struct WelcomePreview {
+    asteroids: Vec<Asteroid>,
+}
+
+impl WelcomePreview {
+    fn new() -> Self {
+        // Cosmetic only, so it doesn't draw from the (possibly seeded) game RNG
+        let mut rng = rand::thread_rng();
+        let asteroids = (0..12)
+            .map(|_| Asteroid {
+                x: rng.gen_range(0..FIELD_WIDTH),
+                y: rng.gen_range(0..FIELD_HEIGHT),
+                vx: -1,
+                vy: 0,
+            })
+            .collect();
+        WelcomePreview { asteroids }
+    }
+
+    fn advance(&mut self) {
+        let bounds = Rect { x: 0, y: 0, w: FIELD_WIDTH, h: FIELD_HEIGHT };
+        movement_system(&mut self.asteroids, &bounds);
+    }
+}
+
+// Perf mode skips a redraw that comes too soon after the last one, so long as
+// the loop is `back_soon` to catch up: a key is already waiting, or the wait
+// for one times out
+fn redraw_due(perf: bool, since_redraw: Option<Duration>, back_soon: bool) -> bool {
+    !perf || !back_soon || since_redraw.is_none_or(|t| t >= PERF_REDRAW_INTERVAL)
+}
+
+// Seed of the attract loop's demo run, so it always plays the same way
+const DEMO_SEED: u64 = 0x5EED;
+
+// The attract loop's demo: the autopilot flying a fixed run in the player's
+// configured game, minus anything that touches files or the screen's banner
+fn demo_state(settings: &Settings, arena: &Arena) -> GameState {
+    let mut state = GameState::new(
+        Settings { seed: Some(DEMO_SEED), skip_intro: true, hardcore: false, ..settings.clone() },
+        arena.clone(),
+    );
+    state.autopilot = true;
+    state
+}
+
+// Cells around the ship kept clear when placing starting asteroids
+const SHIP_CLEARANCE: u16 = 2;
+// Random cells tried for a spawn before giving up on it
+const SPAWN_ATTEMPTS: u32 = 20;
+
+// Starting layout: the fixed resource nodes plus `asteroid_count` asteroids
+// scattered clear of the ships, the resources and the arena's walls
+fn new_world(
+    rng: &mut StdRng,
+    bounds: &Rect,
+    ships: &[&Ship],
+    arena: &Arena,
+    asteroid_count: u32,
+) -> (Vec<Asteroid>, Vec<ResourceNode>) {
     let mut resources = vec![
-        ResourceNode { x: 8, y: 3, kind: Resource::Iron },
-        ResourceNode { x: 25, y: 10, kind: Resource::Crystal },
-        ResourceNode { x: 12, y: 7, kind: Resource::Gold },
+        ResourceNode::new(8, 3, Resource::Iron, Duration::ZERO),
+        ResourceNode::new(25, 10, Resource::Crystal, Duration::ZERO),
+        ResourceNode::new(12, 7, Resource::Gold, Duration::ZERO),
     ];
-    let mut score = 0;
-    let mut tick: u32 = 0;
-    let mut spawn_rate: u32 = 50; // Lower is faster
+    resources.retain(|r| !arena.is_wall(r.x, r.y));
+    let mut asteroids: Vec<Asteroid> = Vec::new();
+    let max_attempts = asteroid_count * 20;
+    let mut attempts = 0;
+    while asteroids.len() < asteroid_count as usize && attempts < max_attempts {
+        attempts += 1;
+        let x = rng.gen_range(bounds.x..bounds.x + bounds.w);
+        let y = rng.gen_range(bounds.y..bounds.y + bounds.h);
+        let near_ship = ships.iter()
+            .any(|s| x.abs_diff(s.x) <= SHIP_CLEARANCE && y.abs_diff(s.y) <= SHIP_CLEARANCE);
+        let taken = arena.is_wall(x, y)
+            || (x, y) == STATION
+            || resources.iter().any(|r| r.x == x && r.y == y)
+            || asteroids.iter().any(|a| a.x == x && a.y == y);
+        if !near_ship && !taken {
+            asteroids.push(Asteroid { x, y, vx: 0, vy: 0 });
+        }
+    }
+    (asteroids, resources)
+}
 
-    // Show welcome screen
-    execute!(stdout, terminal::Clear(ClearType::All), cursor::MoveTo(0, 0)).unwrap();
-    println!("╔════════════════════════════════════╗");
-    println!("║      RUSTY SPACE MINER            ║");
-    println!("║------------------------------------║");
-    println!("║  Use WASD to move, SPACE to mine   ║");
-    println!("║  Avoid asteroids!                  ║");
-    println!("║  Press Q to quit                   ║");
-    println!("╚════════════════════════════════════╝");
-    println!();
-    println!("Press any key to start...");
-    // Wait for any key
-    loop {
-        if event::poll(Duration::from_millis(10)).unwrap() {
-            if let Event::Key(_) = event::read().unwrap() {
-                break;
+// --- Timed Events ---
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum ActiveEvent {
+    // Resources spawn every tick and asteroids freeze until `ends_at`
+    ResourceRain { ends_at: Duration },
+}
+
+// --- Boss ---
+// Bosses are a square block of asteroids this many cells across
+const BOSS_SIZE: u16 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl Edge {
+    fn name(self) -> &'static str {
+        match self {
+            Edge::Top => "NORTH",
+            Edge::Bottom => "SOUTH",
+            Edge::Left => "WEST",
+            Edge::Right => "EAST",
+        }
+    }
+
+    // Velocity carrying an asteroid from this edge into the field
+    fn inward(self) -> (i16, i16) {
+        match self {
+            Edge::Top => (0, 1),
+            Edge::Bottom => (0, -1),
+            Edge::Left => (1, 0),
+            Edge::Right => (-1, 0),
+        }
+    }
+}
+
+// A boss waiting to enter; until `spawns_at` the player only gets a warning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct BossWarning {
+    spawns_at: Duration,
+    edge: Edge,
+}
+
+// Resource rain drops a node per tick until the field holds this many
+const RAIN_MAX_NODES: usize = 24;
+
+// Mines at most this far apart keep a combo going
+const COMBO_WINDOW: Duration = Duration::from_secs(3);
+
+// Clustered spawning drops up to this many asteroids within this many cells
+// of one another
+const CLUSTER_SIZE: u32 = 3;
+const CLUSTER_RADIUS: u16 = 2;
+
+// Resource nodes practice mode keeps on the field
+const PRACTICE_NODES: usize = 3;
+
+// --- Game State ---
+struct GameState {
+    settings: Settings,
+    mode: GameMode,
+    seed: u64,
+    rng: StdRng,
+    // Every input fed to `step`, for high-score verification
+    input_log: String,
+    ship: Ship,
+    // Second player's ship in two-player mode
+    ally: Option<Ship>,
+    // The player's collector drone, with the Drone upgrade
+    drone: Option<Drone>,
+    // Current playfield; grows into the HUD's rows while the HUD is hidden,
+    // unless the world is bigger than the screen
+    bounds: Rect,
+    // The part of the world on screen
+    camera: Rect,
+    arena: Arena,
+    hud_visible: bool,
+    autopilot: bool,
+    paused: bool,
+    // Direction the ship drifts in one-button mode
+    heading: InputEvent,
+    // Ship position at each tick so far, and the previous run's, to race against
+    path: Vec<(u16, u16)>,
+    ghost: Option<Ghost>,
+    asteroids: Vec<Asteroid>,
+    resources: Vec<ResourceNode>,
+    score: u32,
+    // Credits earned this run, banked to the save slot when it ends
+    credits: u32,
+    // Mines chained so far, each within COMBO_WINDOW of the last
+    combo: u32,
+    last_mine_at: Option<Duration>,
+    stats: RunStats,
+    death_cause: Option<DeathCause>,
+    // Resources mined during the latest step, for sound cues
+    mined: Vec<Resource>,
+    tick: u32,
+    elapsed: Duration,
+    // Extra ramp steps from DifficultyBump milestones
+    difficulty_bumps: u32,
+    milestones_reached: usize,
+    active_event: Option<ActiveEvent>,
+    boss_warning: Option<BossWarning>,
+    // Checkpoints still to pass on the time-trial level
+    trial: Option<TimeTrial>,
+}
+
+impl GameState {
+    fn new(mut settings: Settings, arena: Arena) -> Self {
+        settings.milestones.sort_by_key(|m| m.score);
+        // Always seeded, so every run can be recorded alongside its seed
+        let seed = settings.seed.unwrap_or_else(rand::random);
+        let mut ship = Ship::new();
+        if settings.level == LevelType::Scrolling {
+            // Sit near the bottom, facing the oncoming field
+            ship.x = PLAYFIELD.w / 2;
+            ship.y = PLAYFIELD.h - 2;
+        }
+        // Names are checked by Settings::validate
+        ship.upgrades = settings
+            .starting_upgrades
+            .iter()
+            .filter_map(|name| upgrades::find_upgrade(name, &settings.upgrades))
+            .collect();
+        ship.tanks = FuelTanks {
+            main: settings.fuel_capacity,
+            reserve: settings.reserve_tank,
+            reserve_refill_rate: settings.reserve_refill_rate,
+        };
+        ship.fuel = ship.max_fuel();
+        ship.reserve_fuel = ship.tanks.reserve;
+        ship.cargo_capacity = settings.cargo_capacity;
+        ship.shield_charges = upgrades::shield_charges(&ship.upgrades);
+        let ally = settings.two_player.then(|| Ship { x: ship.x + ALLY_OFFSET, ..ship.clone() });
+        let drone = upgrades::has_effect(&ship.upgrades, &Effect::Drone).then(|| Drone::new(ship.x, ship.y));
+        let ship_start = (ship.x, ship.y);
+        let mut rng = StdRng::seed_from_u64(seed);
+        let asteroid_count = resolve_difficulty(&settings, Duration::ZERO, 0).starting_asteroids;
+        let ships: Vec<&Ship> = std::iter::once(&ship).chain(ally.as_ref()).collect();
+        // A world bigger than the screen scrolls with the ship
+        let bounds = match settings.world_size {
+            Some((w, h)) => Rect { w: w.max(PLAYFIELD.w), h: h.max(PLAYFIELD.h), ..PLAYFIELD },
+            None => PLAYFIELD,
+        };
+        let (asteroids, resources) = new_world(&mut rng, &bounds, &ships, &arena, asteroid_count);
+        let trial = (settings.level == LevelType::TimeTrial).then(|| TimeTrial::new(checkpoint_gates(&bounds)));
+        GameState {
+            mode: initial_mode(&settings),
+            settings,
+            seed,
+            rng,
+            input_log: String::new(),
+            ship,
+            ally,
+            drone,
+            bounds,
+            camera: PLAYFIELD,
+            arena,
+            hud_visible: true,
+            autopilot: false,
+            paused: false,
+            heading: InputEvent::Right,
+            path: vec![(ship_start.0, ship_start.1)],
+            ghost: None,
+            asteroids,
+            resources,
+            score: 0,
+            credits: 0,
+            combo: 0,
+            last_mine_at: None,
+            stats: RunStats::default(),
+            mined: Vec::new(),
+            death_cause: None,
+            tick: 0,
+            elapsed: Duration::ZERO,
+            difficulty_bumps: 0,
+            milestones_reached: 0,
+            active_event: None,
+            boss_warning: None,
+            trial,
+        }
+    }
+
+    // A random open cell off the station, or None after a few tries in case
+    // the arena leaves hardly any open space
+    fn random_cell(&mut self) -> Option<(u16, u16)> {
+        let b = self.bounds;
+        for _ in 0..20 {
+            let cell = (self.rng.gen_range(b.x..b.x + b.w), self.rng.gen_range(b.y..b.y + b.h));
+            if !self.arena.is_wall(cell.0, cell.1) && cell != STATION {
+                return Some(cell);
             }
         }
+        None
     }
 
-    loop {
-        render(&ship, &asteroids, &resources, score);
+    fn cargo_value(&self, ship: &Ship) -> u32 {
+        cargo_value(ship, &self.settings.resource_values)
+    }
 
-        let input = read_input().await;
-        if let InputEvent::Quit = input {
-            break;
+    fn resource_value(&self, kind: Resource) -> u32 {
+        self.settings.resource_values.get(&kind).copied().unwrap_or(0)
+    }
+
+    fn summary(&self) -> RunSummary {
+        RunSummary {
+            seed: self.seed,
+            difficulty: self.settings.difficulty,
+            level: self.settings.level,
+            score: self.score,
+            stats: self.stats.clone(),
+            death_cause: self.death_cause,
+            duration_secs: self.elapsed.as_secs_f64(),
+            victory: self.mode == GameMode::Victory,
         }
+    }
 
-        physics_system(&input, &mut ship);
+    fn autopilot_input(&self) -> InputEvent {
+        autopilot(&self.ship, &self.asteroids, &self.resources, &self.bounds, &self.arena, self.settings.fuel_reserve)
+    }
 
-        // Asteroid Spawning
-        tick += 1;
-        if tick % spawn_rate == 0 {
-            use rand::Rng;
-            let mut rng = rand::thread_rng();
-            let new_x = rng.gen_range(0..32);
-            let new_y = rng.gen_range(0..15);
-            asteroids.push(Asteroid { x: new_x, y: new_y });
-        }
-        // Increase Difficulty 
-        if tick % 500 == 0 && spawn_rate > 10 {
-            spawn_rate -= 5; // Asteroids spawn more frequently
-        }
-
-        if collision_system(&ship, &asteroids) || ship.fuel <= 0.0 {
-            render(&ship, &asteroids, &resources, score);
-            //This isn't working, I need to check this, I think it's something to do with the game loop ending and clearing the terminal
-            println!("Game Over! Final Score: {}", score);
-            break;
+    // One-button mode: the ship drifts along its heading, stopping on
+    // resource nodes. The button mines a node under the ship, trades cargo
+    // at the station, and otherwise turns the ship clockwise.
+    fn one_button_input(&mut self, pressed: bool) -> InputEvent {
+        let on_node = self.resources.iter().any(|r| (r.x, r.y) == (self.ship.x, self.ship.y));
+        if on_node {
+            return if pressed { InputEvent::Mine } else { InputEvent::None };
+        }
+        if pressed {
+            if self.docked() && self.ship.cargo.values().any(|&n| n > 0) {
+                return InputEvent::Convert;
+            }
+            self.heading = match (self.heading, self.settings.level) {
+                // The scrolling level only steers left and right
+                (InputEvent::Left, LevelType::Scrolling) => InputEvent::Right,
+                (_, LevelType::Scrolling) => InputEvent::Left,
+                (InputEvent::Up, _) => InputEvent::Right,
+                (InputEvent::Right, _) => InputEvent::Down,
+                (InputEvent::Down, _) => InputEvent::Left,
+                _ => InputEvent::Up,
+            };
+        }
+        self.heading
+    }
+
+    fn saved_run(&self) -> SavedRun {
+        SavedRun {
+            seed: self.seed,
+            score: self.score,
+            credits: self.credits,
+            tick: self.tick,
+            elapsed: self.elapsed,
+            ship: self.ship.clone(),
+            asteroids: self.asteroids.clone(),
+            resources: self.resources.clone(),
+            milestones_reached: self.milestones_reached,
+            difficulty_bumps: self.difficulty_bumps,
+            active_event: self.active_event,
+            boss_warning: self.boss_warning,
+            stats: self.stats.clone(),
+            combo: self.combo,
+            last_mine_at: self.last_mine_at,
+            path: self.path.clone(),
+            input_log: self.input_log.clone(),
+        }
+    }
+
+    // A saved run only carries the player's ship, so runs with a second ship,
+    // a drone or a time trial can't be saved
+    fn can_save(&self) -> bool {
+        self.ally.is_none() && self.drone.is_none() && self.trial.is_none()
+    }
+
+    // Picks a saved run back up. Spawns from here on draw from a fresh stream
+    // off the run's seed rather than the exact one the run was using.
+    fn resume(&mut self, run: SavedRun) {
+        self.seed = run.seed;
+        self.rng = StdRng::seed_from_u64(run.seed ^ run.tick as u64);
+        self.score = run.score;
+        self.credits = run.credits;
+        self.tick = run.tick;
+        self.elapsed = run.elapsed;
+        self.ship = run.ship;
+        self.asteroids = run.asteroids;
+        self.resources = run.resources;
+        self.milestones_reached = run.milestones_reached;
+        self.difficulty_bumps = run.difficulty_bumps;
+        self.active_event = run.active_event;
+        self.boss_warning = run.boss_warning;
+        self.stats = run.stats;
+        self.combo = run.combo;
+        self.last_mine_at = run.last_mine_at;
+        // Saves from before paths were kept start the ghost's path afresh
+        self.path = if run.path.is_empty() { vec![(self.ship.x, self.ship.y)] } else { run.path };
+        self.input_log = run.input_log;
+        self.follow_ship();
+    }
+
+    fn autopilot_status(&self) -> Option<AutopilotMode> {
+        self.autopilot.then(|| autopilot_mode(&self.ship, &self.asteroids, &self.resources, self.settings.fuel_reserve))
+    }
+
+    fn hud_rows(&self) -> u16 {
+        HUD_ROWS + self.ally.is_some() as u16
+    }
+
+    // Hiding the HUD hands its rows to the playfield; showing it takes them back
+    fn toggle_hud(&mut self) {
+        self.hud_visible = !self.hud_visible;
+        self.camera.h = PLAYFIELD.h + if self.hud_visible { 0 } else { self.hud_rows() };
+        if self.settings.world_size.is_none() {
+            self.bounds.h = self.camera.h;
+            self.clamp_entities();
         }
+        self.follow_ship();
+    }
+
+    fn follow_ship(&mut self) {
+        self.camera = camera_follow(self.camera, &self.ship, self.settings.camera_deadzone, &self.bounds);
+    }
 
-        if let Some(_mined) = mining_system(&input, &mut ship, &mut resources) {
-            score += 10;
+    fn clamp_entities(&mut self) {
+        let b = self.bounds;
+        for ship in std::iter::once(&mut self.ship).chain(self.ally.as_mut()) {
+            (ship.x, ship.y) = clamp_point(ship.x as i32, ship.y as i32, &b);
         }
+        for a in &mut self.asteroids {
+            (a.x, a.y) = clamp_point(a.x as i32, a.y as i32, &b);
+        }
+        for r in &mut self.resources {
+            (r.x, r.y) = clamp_point(r.x as i32, r.y as i32, &b);
+        }
+    }
 
-        tokio::time::sleep(Duration::from_millis(80)).await;
+    // Advance the simulation by one frame
+    fn step(&mut self, input: &InputEvent, dt: Duration) {
+        self.step_with_ally(input, input, dt);
     }
 
-    // Restore terminal
-    execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen).unwrap();
-    terminal::disable_raw_mode().unwrap();
-}
\ No newline at end of file
+    // A tick where player two's input came separately from player one's; each
+    // player's systems ignore the other's keys
+    fn step_with_ally(&mut self, input: &InputEvent, ally: &InputEvent, dt: Duration) {
+        // A paused game is frozen; only the renderer's animation clock runs on
+        if self.mode != GameMode::Playing || self.paused {
+            return;
+        }
+        self.elapsed += dt;
+        // One code per tick, followed by player two's when both acted
+        if *input != InputEvent::None || ally == input {
+            self.input_log.push(input.code());
+        }
+        if ally != input && *ally != InputEvent::None {
+            self.input_log.push(ally.code());
+        }
+        self.mined.clear();
+
+        let scrolling = self.settings.level == LevelType::Scrolling;
+        // The scrolling level only steers left and right
+        let steer = |input: InputEvent| match input.delta() {
+            (dx, dy) if scrolling && dy != 0 => InputEvent::from_delta((dx, 0)),
+            _ => input,
+        };
+        let ally_input = steer(ally.ally_action());
+        let input = &steer(*input);
+
+        let params = self.difficulty();
+        let start = (self.ship.x, self.ship.y);
+        physics_system(input, &mut self.ship, &self.bounds, &self.arena, params.fuel_drain);
+        self.follow_ship();
+        // The path only feeds the next run's ghost, a cosmetic
+        if self.settings.effects_enabled() {
+            self.path.push((self.ship.x, self.ship.y));
+        }
+        if let Some(trial) = self.trial.as_mut() {
+            trial.update(&ship_hitbox(&self.ship), self.elapsed);
+        }
+        if input.delta() != (0, 0) {
+            self.stats.moves += 1;
+        }
+        self.stats.distance += (self.ship.x.abs_diff(start.0) + self.ship.y.abs_diff(start.1)) as u32;
+        if let Some(ally) = self.ally.as_mut() {
+            physics_system(&ally_input, ally, &self.bounds, &self.arena, params.fuel_drain);
+            // Fuel only flows while both ships hold still
+            let holding_still = input.delta() == (0, 0) && ally_input.delta() == (0, 0);
+            if holding_still && ships_adjacent(&self.ship, ally) {
+                match input {
+                    InputEvent::Tether => { transfer_fuel(&mut self.ship, ally, dt); }
+                    InputEvent::AllyTether => { transfer_fuel(ally, &mut self.ship, dt); }
+                    _ => {}
+                }
+            }
+        }
+        self.event_system();
+        self.boss_system();
+        let raining = self.resource_rain();
+        if !raining {
+            movement_system(&mut self.asteroids, &self.bounds);
+        }
+        let lifetime = self.resource_lifetime();
+        expiry_system(&mut self.resources, self.elapsed, lifetime);
+        for ship in std::iter::once(&self.ship).chain(self.ally.as_ref()) {
+            magnet_system(ship, &mut self.resources, &self.asteroids, &self.arena, self.settings.magnet_strength);
+        }
+
+        // Asteroid Spawning
+        self.tick += 1;
+        let moves = [input.delta(), ally_input.delta()];
+        if scrolling {
+            // A ship stepping into an asteroid hits it before the asteroid scrolls on past
+            let cause = self.collide(moves, false);
+            if cause.is_some() {
+                self.death_cause = cause;
+                self.mode = GameMode::GameOver;
+                return;
+            }
+            scroll_system(&mut self.asteroids, &mut self.resources, &self.bounds, raining);
+            self.spawn_top_edge(params.spawn_rate);
+        } else if self.tick.is_multiple_of(params.spawn_rate) && self.can_spawn_asteroid() {
+            match self.settings.asteroid_spawn {
+                SpawnPattern::Uniform => {
+                    if let Some((new_x, new_y)) = self.asteroid_cell() {
+                        self.asteroids.push(Asteroid { x: new_x, y: new_y, vx: 0, vy: 0 });
+                    }
+                }
+                SpawnPattern::Clustered => self.spawn_cluster(),
+            }
+        }
+        if raining {
+            self.rain_resources();
+        }
+
+        let mut cause = self.collide(moves, true);
+        for ship in std::iter::once(&self.ship).chain(self.ally.as_ref()) {
+            if ship.hull <= 0.0 {
+                cause = cause.or(Some(DeathCause::HullBreached));
+            }
+            if ship.total_fuel() <= 0.0 {
+                cause = cause.or(Some(DeathCause::OutOfFuel));
+            }
+        }
+        if cause.is_some() {
+            self.death_cause = cause;
+            self.mode = GameMode::GameOver;
+            return;
+        }
+
+        if *input == InputEvent::Convert {
+            self.station_convert();
+        }
+        if let Some(mined) = mining_system(input, &mut self.ship, &mut self.resources, &self.settings, params.fuel_value_scale, &mut self.rng) {
+            self.stats.mines += 1;
+            self.award_mine(mined);
+        }
+        let ally_mined = self.ally.as_mut()
+            .and_then(|ally| mining_system(&ally_input, ally, &mut self.resources, &self.settings, params.fuel_value_scale, &mut self.rng));
+        if let Some(mined) = ally_mined {
+            self.award_mine(mined);
+        }
+        let settings = &self.settings;
+        let drone_mined = self.drone.as_mut()
+            .and_then(|drone| drone_system(drone, &mut self.ship, &mut self.resources, settings, params.fuel_value_scale));
+        if let Some(mined) = drone_mined {
+            self.award_mine(mined);
+        }
+        self.milestone_system();
+        if self.settings.practice {
+            self.practice_respawn();
+        }
+        if self.goal_reached() {
+            self.mode = GameMode::Victory;
+        } else if self.cap_reached() {
+            self.mode = GameMode::TimeUp;
+        }
+    }
+
+    // Asteroid strikes on either ship: fatal, or with knockback a loss of hull
+    // and a shove clear. Grazes wear the hull down too when `grazes` is set.
+    fn collide(&mut self, moves: [(i32, i32); 2], grazes: bool) -> Option<DeathCause> {
+        let mode = self.difficulty().collision;
+        let mut cause = None;
+        let mut shattered = Vec::new();
+        for (ship, ship_move) in std::iter::once(&mut self.ship).chain(self.ally.as_mut()).zip(moves) {
+            match (collision_system(ship, &self.asteroids, self.settings.graze_radius), mode) {
+                (Collision::Core(i), _) if ship.shield_charges > 0 => {
+                    ship.shield_charges -= 1;
+                    shattered.push(i);
+                }
+                (Collision::Core(i), CollisionMode::Knockback(distance)) => {
+                    ship.hull = (ship.hull - KNOCKBACK_DAMAGE).max(0.0);
+                    let direction = knockback_direction(&self.asteroids[i], ship_move);
+                    knockback(ship, direction, distance, &self.bounds, &self.arena, &self.asteroids);
+                }
+                (Collision::Core(_), CollisionMode::Fatal) => cause = cause.or(Some(DeathCause::Asteroid)),
+                (Collision::Graze(count), _) if grazes => {
+                    ship.hull = (ship.hull - self.settings.graze_damage * count as f32).max(0.0);
+                }
+                (Collision::Graze(_) | Collision::None, _) => {}
+            }
+        }
+        // Asteroids that hit a shield break up
+        shattered.sort_unstable();
+        shattered.dedup();
+        for i in shattered.into_iter().rev() {
+            self.asteroids.remove(i);
+        }
+        cause
+    }
+
+    // Scores a mined node; mines in quick succession build up a combo
+    fn award_mine(&mut self, kind: Resource) {
+        let chained = self.last_mine_at.is_some_and(|t| self.elapsed.saturating_sub(t) <= COMBO_WINDOW);
+        self.combo = if chained { self.combo + 1 } else { 0 };
+        self.last_mine_at = Some(self.elapsed);
+        self.score += score_for_mine(kind, self.combo, self.difficulty().score_percent, self.settings.scoring);
+        self.credits += self.resource_value(kind);
+        self.mined.push(kind);
+    }
+
+    fn docked(&self) -> bool {
+        (self.ship.x, self.ship.y) == STATION
+    }
+
+    // Converts one unit of the cheapest resource in the hold
+    fn station_convert(&mut self) {
+        if !self.docked() {
+            return;
+        }
+        let cheapest = [Resource::Iron, Resource::Crystal, Resource::Gold].into_iter()
+            .filter(|kind| self.ship.cargo.get(kind).is_some_and(|&n| n > 0))
+            .min_by_key(|&kind| self.resource_value(kind));
+        if let Some(kind) = cheapest {
+            let _ = convert_to_fuel(&mut self.ship, kind, 1, self.settings.fuel_exchange_rate);
+        }
+    }
+
+    // Safety cap so automated runs always finish
+    fn cap_reached(&self) -> bool {
+        self.settings.max_ticks.is_some_and(|max| self.tick >= max)
+            || self.settings.max_seconds.is_some_and(|max| self.elapsed.as_secs_f32() >= max)
+    }
+
+    fn goal_reached(&self) -> bool {
+        if self.trial.as_ref().is_some_and(TimeTrial::finished) {
+            return true;
+        }
+        match self.settings.win_condition {
+            Some(WinCondition::Score(score)) => self.score >= score,
+            Some(WinCondition::SurviveSecs(secs)) => self.elapsed.as_secs_f32() >= secs,
+            Some(WinCondition::Collect { resource, count }) => {
+                self.ship.cargo.get(&resource).copied().unwrap_or(0) >= count
+            }
+            None => false,
+        }
+    }
+
+    // Feeds the scrolling level from the top row; asteroids get more frequent
+    // as the difficulty ramp lowers the spawn rate
+    fn spawn_top_edge(&mut self, spawn_rate: u32) {
+        let Some((x, _)) = self.random_cell() else { return };
+        let top = self.bounds.y;
+        if self.arena.is_wall(x, top) {
+            return;
+        }
+        if self.rng.gen_range(0..spawn_rate) < 10 && self.can_spawn_asteroid() {
+            if self.clear_of_ships(x, top, self.settings.min_spawn_distance) {
+                self.asteroids.push(Asteroid { x, y: top, vx: 0, vy: 0 });
+            }
+        } else if self.rng.gen_ratio(1, 8) {
+            if !self.free_for_resource(x, top) {
+                self.stats.spawn_failures += 1;
+                return;
+            }
+            let kind = self.random_resource();
+            let node = self.spawn_node(x, top, kind);
+            self.resources.push(node);
+        }
+    }
+
+    // A tight group of asteroids around a random point, keeping clear of the ships
+    fn spawn_cluster(&mut self) {
+        let Some((cx, cy)) = self.random_cell() else { return };
+        let r = CLUSTER_RADIUS as i32;
+        for _ in 0..CLUSTER_SIZE {
+            let dx = self.rng.gen_range(-r..=r);
+            let dy = self.rng.gen_range(-r..=r);
+            let (x, y) = clamp_point(cx as i32 + dx, cy as i32 + dy, &self.bounds);
+            let near_ship = !self.clear_of_ships(x, y, self.settings.min_spawn_distance.max(SHIP_CLEARANCE + 1));
+            let taken = self.arena.is_wall(x, y)
+                || (x, y) == STATION
+                || self.asteroids.iter().any(|a| (a.x, a.y) == (x, y));
+            if !near_ship && !taken {
+                self.asteroids.push(Asteroid { x, y, vx: 0, vy: 0 });
+            }
+        }
+    }
+
+    // Whether (x, y) is at least `distance` cells from every ship, in any direction
+    fn clear_of_ships(&self, x: u16, y: u16, distance: u16) -> bool {
+        std::iter::once(&self.ship).chain(self.ally.as_ref())
+            .all(|s| x.abs_diff(s.x).max(y.abs_diff(s.y)) >= distance)
+    }
+
+    // A random cell far enough from the ships for a new asteroid, or None if
+    // none turned up, in which case the spawn is skipped
+    fn asteroid_cell(&mut self) -> Option<(u16, u16)> {
+        for _ in 0..SPAWN_ATTEMPTS {
+            let Some((x, y)) = self.random_cell() else { continue };
+            if self.clear_of_ships(x, y, self.settings.min_spawn_distance) {
+                return Some((x, y));
+            }
+        }
+        None
+    }
+
+    // Resources never appear under a ship, an asteroid, another node, the
+    // station or a wall
+    fn free_for_resource(&self, x: u16, y: u16) -> bool {
+        let under_ship = std::iter::once(&self.ship).chain(self.ally.as_ref())
+            .any(|s| y == s.y && (s.x..s.x + SHIP_WIDTH).contains(&x));
+        !under_ship
+            && !self.arena.is_wall(x, y)
+            && (x, y) != STATION
+            && !self.asteroids.iter().any(|a| (a.x, a.y) == (x, y))
+            && !self.resources.iter().any(|r| (r.x, r.y) == (x, y))
+    }
+
+    // A random free cell for a new resource node, or None (counted in the run's
+    // stats) if none turned up, in which case the spawn is skipped
+    fn resource_cell(&mut self) -> Option<(u16, u16)> {
+        for _ in 0..SPAWN_ATTEMPTS {
+            let Some((x, y)) = self.random_cell() else { continue };
+            if self.free_for_resource(x, y) {
+                return Some((x, y));
+            }
+        }
+        self.stats.spawn_failures += 1;
+        None
+    }
+
+    fn resource_lifetime(&self) -> Duration {
+        Duration::from_secs_f32(self.settings.resource_lifetime_secs.max(0.0))
+    }
+
+    // Nodes flicker to a faded glyph in the last quarter of their lifetime
+    fn resource_fading(&self, node: &ResourceNode) -> bool {
+        let lifetime = self.resource_lifetime();
+        !lifetime.is_zero() && self.elapsed.saturating_sub(node.spawned_at) >= lifetime.mul_f32(0.75)
+    }
+
+    // (ticks done, ticks needed) while the player is partway through mining a node
+    fn mining_progress(&self) -> Option<(u32, u32)> {
+        let ship = &self.ship;
+        if ship.mine_progress == 0 {
+            return None;
+        }
+        let node = self.resources.iter().find(|r| r.x == ship.x && r.y == ship.y)?;
+        Some((ship.mine_progress, mine_duration(node.kind, &ship.upgrades)))
+    }
+
+    fn field_is_dense(&self) -> bool {
+        field_density(&self.asteroids, &self.bounds) >= self.settings.dense_field_threshold
+    }
+
+    // Spawning pauses at the difficulty's asteroid cap so the field can't pile
+    // up into an unwinnable wall, and never happens during resource rain
+    fn can_spawn_asteroid(&self) -> bool {
+        if self.resource_rain() {
+            return false;
+        }
+        self.difficulty().asteroid_cap.is_none_or(|cap| field_density(&self.asteroids, &self.bounds) < cap)
+    }
+
+    // Practice mode keeps the field stocked so there is always something to mine
+    fn practice_respawn(&mut self) {
+        while self.resources.len() < PRACTICE_NODES {
+            // A field too crowded to restock waits for the next tick
+            let Some((x, y)) = self.resource_cell() else { break };
+            let kind = self.random_resource();
+            let node = self.spawn_node(x, y, kind);
+            self.resources.push(node);
+        }
+    }
+
+    fn resource_rain(&self) -> bool {
+        matches!(self.active_event, Some(ActiveEvent::ResourceRain { .. }))
+    }
+
+    // Ends the active event once its time is up, handing spawning back to normal
+    fn event_system(&mut self) {
+        match self.active_event {
+            Some(ActiveEvent::ResourceRain { ends_at }) if self.elapsed >= ends_at => self.active_event = None,
+            _ => {}
+        }
+    }
+
+    // Sends in a warned-about boss once its warning has run out
+    fn boss_system(&mut self) {
+        if let Some(warning) = self.boss_warning {
+            if self.elapsed >= warning.spawns_at {
+                self.boss_warning = None;
+                self.spawn_boss(warning.edge);
+            }
+        }
+    }
+
+    // Places the boss at a random point along `edge`, heading inwards
+    fn spawn_boss(&mut self, edge: Edge) {
+        let b = self.bounds;
+        let along = |rng: &mut StdRng, len: u16| rng.gen_range(0..len.saturating_sub(BOSS_SIZE).max(1));
+        let (left, top) = match edge {
+            Edge::Top => (b.x + along(&mut self.rng, b.w), b.y),
+            Edge::Bottom => (b.x + along(&mut self.rng, b.w), (b.y + b.h).saturating_sub(BOSS_SIZE)),
+            Edge::Left => (b.x, b.y + along(&mut self.rng, b.h)),
+            Edge::Right => ((b.x + b.w).saturating_sub(BOSS_SIZE), b.y + along(&mut self.rng, b.h)),
+        };
+        let (vx, vy) = edge.inward();
+        for dy in 0..BOSS_SIZE {
+            for dx in 0..BOSS_SIZE {
+                let (x, y) = (left + dx, top + dy);
+                if !self.arena.is_wall(x, y) && (x, y) != STATION && self.clear_of_ships(x, y, 1) {
+                    self.asteroids.push(Asteroid { x, y, vx, vy });
+                }
+            }
+        }
+    }
+
+    fn rain_resources(&mut self) {
+        if self.resources.len() >= RAIN_MAX_NODES {
+            return;
+        }
+        if let Some((x, y)) = self.resource_cell() {
+            let kind = self.random_resource();
+            let node = self.spawn_node(x, y, kind);
+            self.resources.push(node);
+        }
+    }
+
+    // A freshly spawned node, sometimes a rich or contaminated one
+    fn spawn_node(&mut self, x: u16, y: u16, kind: Resource) -> ResourceNode {
+        let mut node = ResourceNode::new(x, y, kind, self.elapsed);
+        // Only rolled when enabled, so seeded runs without rich nodes play out as before
+        if self.settings.rich_node_chance > 0.0 && self.rng.gen_bool(self.settings.rich_node_chance.min(1.0)) {
+            node = node.rich(self.settings.rich_node_amount.max(1));
+        }
+        let chance = self.settings.contamination_chance;
+        node.contaminated = chance > 0.0 && self.rng.gen_bool(chance.min(1.0));
+        node
+    }
+
+    fn random_resource(&mut self) -> Resource {
+        match self.rng.gen_range(0..3) {
+            0 => Resource::Iron,
+            1 => Resource::Crystal,
+            _ => Resource::Gold,
+        }
+    }
+
+    // The difficulty curve right now, with settings overrides and milestone
+    // bumps on top. The ramp runs on game time, so it escalates at the same
+    // pace however fast frames (or keypresses) come in.
+    fn difficulty(&self) -> DifficultyParams {
+        resolve_difficulty(&self.settings, self.elapsed, self.difficulty_bumps)
+    }
+
+    // Fire each milestone the score has crossed, exactly once and in order
+    fn milestone_system(&mut self) {
+        while let Some(milestone) = self.settings.milestones.get(self.milestones_reached) {
+            if self.score < milestone.score {
+                break;
+            }
+            let event = milestone.event;
+            self.milestones_reached += 1;
+            self.trigger_event(event);
+        }
+    }
+
+    fn trigger_event(&mut self, event: MilestoneEvent) {
+        match event {
+            MilestoneEvent::SupplyDrop => {
+                for kind in [Resource::Crystal, Resource::Iron, Resource::Gold] {
+                    let Some((x, y)) = self.resource_cell() else { continue };
+                    let node = self.spawn_node(x, y, kind);
+                    self.resources.push(node);
+                }
+            }
+            MilestoneEvent::MeteorShower => {
+                for _ in 0..5 {
+                    if !self.can_spawn_asteroid() {
+                        break;
+                    }
+                    if let Some((x, y)) = self.asteroid_cell() {
+                        self.asteroids.push(Asteroid { x, y, vx: 0, vy: 0 });
+                    }
+                }
+            }
+            MilestoneEvent::DifficultyBump => self.difficulty_bumps += 1,
+            MilestoneEvent::ResourceRain => {
+                let ends_at = self.elapsed + Duration::from_secs_f32(self.settings.resource_rain_secs.max(0.0));
+                self.active_event = Some(ActiveEvent::ResourceRain { ends_at });
+            }
+            MilestoneEvent::Boss => {
+                let edge = [Edge::Top, Edge::Bottom, Edge::Left, Edge::Right][self.rng.gen_range(0..4)];
+                let spawns_at = self.elapsed + Duration::from_secs_f32(self.settings.boss_warning_secs.max(0.0));
+                self.boss_warning = Some(BossWarning { spawns_at, edge });
+            }
+        }
+    }
+}
+
+// Shows last run's route as a ghost if it was flown on the same seed
+fn load_ghost(seed: u64) -> Option<Ghost> {
+    Ghost::load(Path::new(GHOST_FILE)).ok().flatten().filter(|g| g.seed == seed)
+}
+
+fn save_ghost(state: &GameState) {
+    let ghost = Ghost { seed: state.seed, path: state.path.clone() };
+    // Nowhere to report this once the terminal is restored; losing the ghost is harmless
+    let _ = ghost.save(Path::new(GHOST_FILE));
+}
+
+// The game-over screen's last line: how to leave it, and when it closes by itself
+fn closing_line(timeout: Option<Duration>) -> String {
+    match timeout {
+        Some(t) => format!("Closing in {}s, or press any key", t.as_secs_f32().ceil()),
+        None => "Press any key to exit".to_string(),
+    }
+}
+
+// Advice for the next run, tailored to how this one ended
+fn restart_hint(cause: DeathCause, stats: &RunStats) -> &'static str {
+    match cause {
+        DeathCause::OutOfFuel if stats.mines == 0 => "Tip: mine Crystal to top up your fuel",
+        DeathCause::OutOfFuel => "Tip: dock at the station and press C to turn cargo into fuel",
+        DeathCause::Asteroid if stats.distance < 10 => "Tip: keep moving, new asteroids can appear anywhere",
+        DeathCause::Asteroid => "Tip: plan a route around asteroids before heading for a resource",
+        DeathCause::HullBreached => "Tip: grazing asteroids wears down the hull, give them a wider berth",
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum QuitChoice {
+    Resume,
+    Quit,
+    SaveAndQuit,
+}
+
+// Asks before leaving a run: S saves it to the slot first, where the run can
+// be saved, Q quits, anything else goes back to the game. Ctrl-C, or a
+// signal, quits without saving.
+fn confirm_quit<W: std::io::Write>(renderer: &mut Renderer<W>, state: &GameState) -> QuitChoice {
+    let prompt = if state.can_save() { "QUIT? S=SAVE+QUIT Q=QUIT" } else { "QUIT? Q=QUIT" };
+    let lines = renderer.prompt_lines(state, prompt);
+    renderer.present(&lines);
+    loop {
+        if INTERRUPTED.load(Ordering::SeqCst) {
+            return QuitChoice::Quit;
+        }
+        if !event::poll(Duration::from_millis(10)).unwrap() {
+            continue;
+        }
+        if let Event::Key(key) = event::read().unwrap() {
+            return match key.code {
+                _ if is_ctrl_c(&key) => QuitChoice::Quit,
+                KeyCode::Char('q') => QuitChoice::Quit,
+                KeyCode::Char('s') if state.can_save() => QuitChoice::SaveAndQuit,
+                _ => QuitChoice::Resume,
+            };
+        }
+    }
+}
+
+// Stores the run in the save slot at `path`, to be resumed next time the slot is played
+fn save_run(state: &GameState, path: &Path) -> String {
+    let slot = state.settings.save_slot;
+    let result = SaveSlot::load(path).and_then(|mut career| {
+        career.run = Some(state.saved_run());
+        career.save(path)
+    });
+    match result {
+        Ok(()) => format!("Run saved to slot {}; play on slot {} to resume it", slot, slot),
+        Err(e) => e,
+    }
+}
+
+// Split times for a time trial, next to the best so far; a finished trial
+// updates the bests
+fn record_splits(trial: &TimeTrial) -> Vec<String> {
+    let path = Path::new(BEST_SPLITS_FILE);
+    let mut best = match BestSplits::load(path) {
+        Ok(best) => best,
+        Err(e) => return vec![e],
+    };
+    let format_splits = |splits: &mut dyn Iterator<Item = f32>| {
+        splits.map(|s| format!("{:.1}s", s)).collect::<Vec<_>>().join(" ")
+    };
+    let mut lines = vec![format!("Splits: {}", format_splits(&mut trial.splits.iter().map(|s| s.as_secs_f32())))];
+    if best.same_course(&trial.gates) {
+        lines.push(format!("Best:   {}", format_splits(&mut best.splits.iter().copied())));
+    }
+    if trial.finished() {
+        best.merge(&trial.gates, &trial.splits);
+        if let Err(e) = best.save(path) {
+            lines.push(e);
+        }
+    }
+    lines
+}
+
+// Banks the run's credits in the save slot at `path`, or in hardcore mode
+// wipes the slot if the ship was lost. Returns the lines to show for it.
+fn record_career(state: &GameState, path: &Path) -> Vec<String> {
+    let slot = state.settings.save_slot;
+    if state.settings.hardcore && state.death_cause.is_some() {
+        return match SaveSlot::delete(path) {
+            Ok(()) => vec![format!("HARDCORE: save slot {} deleted", slot)],
+            Err(e) => vec![e],
+        };
+    }
+    let mut career = match SaveSlot::load(path) {
+        Ok(career) => career,
+        Err(e) => return vec![e],
+    };
+    career.credits += state.credits as u64;
+    career.runs += 1;
+    let mut lines = vec![format!("Credits earned: {} (slot {} total: {})", state.credits, slot, career.credits)];
+    if let Err(e) = career.save(path) {
+        lines.push(e);
+    }
+    lines
+}
+
+// Adds the run to the high-score board and returns the lines to show for it
+fn record_high_score(state: &GameState) -> Vec<String> {
+    let path = Path::new(HIGHSCORES_FILE);
+    let mut board = match HighScoreBoard::load(path) {
+        Ok(board) => board,
+        Err(e) => return vec![e],
+    };
+    let mut lines = Vec::new();
+    if let Some(rank) = board.insert(HighScore::new(state.score, state.seed, state.input_log.clone())) {
+        lines.push(format!("New high score! Rank #{}", rank));
+        if let Err(e) = board.save(path) {
+            lines.push(e);
+        }
+    }
+    lines.push("HIGH SCORES".to_string());
+    lines.extend(board.lines());
+    lines
+}
+
+#[tokio::main]
+async fn main() {
+    if std::env::args().any(|arg| arg == "--selftest") {
+        std::process::exit(if selftest::run() { 0 } else { 1 });
+    }
+    let mut settings = Settings::load(Path::new(SETTINGS_FILE)).unwrap_or_else(|e| {
+        eprintln!("{}, using defaults", e);
+        Settings::default()
+    });
+    if let Err(e) = settings.apply_args(std::env::args().skip(1)) {
+        eprintln!("{}", e);
+        eprintln!("usage: rusty-space-miner [--skip-intro] [--seed N] [--level field|scrolling|trial] [--difficulty easy|normal|hard] [--spawn uniform|clustered] [--spawn-distance N] [--two-player] [--one-button] [--margin N] [--knockback N] [--fuel-reserve N] [--reserve-tank N] [--cargo-capacity N] [--sound] [--practice] [--slot N] [--hardcore] [--gold-rush] [--auto-mine] [--yield-variance N] [--stats-out FILE] [--history] [--game-over-timeout SECS] [--max-ticks N] [--max-seconds SECS] [--arena FILE] [--win-score N] [--win-time SECS] [--spectate PORT] [--attract SECS] [--perf] [--stars] [--show-ore] [--show-hitboxes] [--no-hints] [--selftest]");
+        std::process::exit(2);
+    }
+    if let Err(e) = settings.validate() {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    }
+    let arena = match &settings.arena {
+        Some(path) => Arena::load(path).and_then(|arena| check_arena(&arena).map(|_| arena)).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }),
+        None => Arena::default(),
+    };
+    let mut spectators = settings.spectate_port.map(|port| {
+        Broadcaster::bind(port).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        })
+    });
+
+    let saved_run = SaveSlot::take_run(&slot_path(settings.save_slot)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+
+    tokio::spawn(async {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            INTERRUPTED.store(true, Ordering::SeqCst);
+        }
+    });
+
+    // Setup terminal
+    let terminal = TerminalGuard::new();
+
+    let mut renderer = Renderer::new(stdout(), settings.hud_refresh);
+    let sound = Sound::new(settings.sound, settings.sound_value_tiers.clone());
+    let mut state = GameState::new(settings, arena);
+    if let Some(run) = saved_run {
+        state.resume(run);
+    }
+    state.ghost = load_ghost(state.seed);
+
+    // Show welcome screen
+    if state.mode == GameMode::Welcome {
+        let mut preview = WelcomePreview::new();
+        let mut attract = state.settings.attract_idle().map(Attract::new);
+        let mut demo: Option<GameState> = None;
+        loop {
+            if INTERRUPTED.load(Ordering::SeqCst) {
+                return;
+            }
+            let lines = match attract.as_ref().map(|a| a.screen) {
+                Some(AttractScreen::HighScores) => {
+                    render::high_score_lines(&HighScoreBoard::load(Path::new(HIGHSCORES_FILE)).unwrap_or_default(), &state.settings)
+                }
+                Some(AttractScreen::Demo) => {
+                    let demo = demo.get_or_insert_with(|| demo_state(&state.settings, &state.arena));
+                    renderer.game_lines(demo)
+                }
+                _ => render::welcome_lines(&preview.asteroids, &state.settings),
+            };
+            renderer.present(&lines);
+            // Wait for any key, drifting the field while idle
+            if event::poll(PREVIEW_FRAME).unwrap() {
+                if let Event::Key(key) = event::read().unwrap() {
+                    if is_ctrl_c(&key) {
+                        return;
+                    }
+                    // During the attract loop a key only brings the menu back
+                    if attract.as_mut().is_some_and(|a| !a.key_pressed()) {
+                        continue;
+                    }
+                    break;
+                }
+            } else {
+                if state.settings.effects_enabled() {
+                    preview.advance();
+                }
+                if let Some(attract) = attract.as_mut() {
+                    attract.advance(PREVIEW_FRAME);
+                    match demo.as_mut() {
+                        // Each showing of the demo starts it afresh
+                        _ if attract.screen != AttractScreen::Demo => demo = None,
+                        Some(run) if run.mode != GameMode::Playing => demo = None,
+                        Some(run) => {
+                            let input = run.autopilot_input();
+                            run.step(&input, PREVIEW_FRAME);
+                        }
+                        None => {}
+                    }
+                }
+            }
+        }
+        state.mode = GameMode::Playing;
+    }
+
+    let mut last_frame = Instant::now();
+    // Set once the run has been saved to be resumed later
+    let mut saved: Option<String> = None;
+    let mut dead_time = MineDeadTime::new(Duration::from_millis(state.settings.mine_dead_time_ms));
+    let mut last_redraw: Option<Instant> = None;
+    while state.mode == GameMode::Playing {
+        // With the autopilot engaged the game keeps ticking between keypresses
+        let timeout = if state.paused {
+            Some(PAUSED_FRAME)
+        } else if state.autopilot {
+            Some(AUTOPILOT_FRAME)
+        } else {
+            state.settings.one_button.then_some(ONE_BUTTON_FRAME)
+        };
+        let back_soon = timeout.is_some() || event::poll(Duration::ZERO).unwrap();
+        if redraw_due(state.settings.perf, last_redraw.map(|t| t.elapsed()), back_soon) {
+            let lines = renderer.game_lines(&state);
+            renderer.present(&lines);
+            last_redraw = Some(Instant::now());
+        }
+        if let Some(spectators) = spectators.as_mut() {
+            spectators.broadcast(&state);
+        }
+
+        let action = coalesce_inputs(&read_inputs(timeout).await);
+        let ally = action.ally_input();
+        let input = match action.input() {
+            InputEvent::None if state.autopilot && !state.paused => state.autopilot_input(),
+            InputEvent::Mine if state.settings.one_button && !state.paused => state.one_button_input(true),
+            InputEvent::None if state.settings.one_button && !state.paused => state.one_button_input(false),
+            input => input,
+        };
+        match input {
+            // Ctrl-C, or a signal from outside, quits straight away
+            InputEvent::Interrupt => break,
+            InputEvent::Quit if state.tick == 0 => break,
+            InputEvent::Quit => match confirm_quit(&mut renderer, &state) {
+                QuitChoice::Resume => {
+                    last_frame = Instant::now();
+                    continue;
+                }
+                QuitChoice::Quit => break,
+                QuitChoice::SaveAndQuit => {
+                    saved = Some(save_run(&state, &slot_path(state.settings.save_slot)));
+                    break;
+                }
+            },
+            InputEvent::ToggleStats => {
+                renderer.show_stats = !renderer.show_stats;
+                continue;
+            }
+            InputEvent::ToggleHud => {
+                state.toggle_hud();
+                continue;
+            }
+            InputEvent::ToggleCargoReadout => {
+                renderer.cargo_readout = renderer.cargo_readout.toggled();
+                continue;
+            }
+            InputEvent::ToggleAutopilot => {
+                state.autopilot = !state.autopilot;
+                continue;
+            }
+            InputEvent::Pause => {
+                state.paused = !state.paused;
+                // Time spent paused doesn't count towards the next step
+                last_frame = Instant::now();
+                continue;
+            }
+            _ if state.paused => continue,
+            _ if dead_time.suppresses(&input, &ally, Instant::now()) => continue,
+            _ => {}
+        }
+
+        let now = Instant::now();
+        state.step_with_ally(&input, &ally, now - last_frame);
+        last_frame = now;
+        for &kind in &state.mined {
+            sound.play(Cue::Mined { value: state.resource_value(kind) });
+        }
+        if !state.mined.is_empty() {
+            dead_time.mined(now);
+        }
+
+        if state.mode != GameMode::Playing {
+            if let Some(spectators) = spectators.as_mut() {
+                spectators.broadcast(&state);
+            }
+            let mut lines = renderer.game_lines(&state);
+            let headline = match state.mode {
+                GameMode::Victory => "You Win!",
+                GameMode::TimeUp => "Time Up!",
+                _ => "Game Over!",
+            };
+            lines.push(format!("{} Final Score: {} (survived {}s)", headline, state.score, state.elapsed.as_secs()));
+            if let Some(cause) = state.death_cause.filter(|_| state.settings.hints) {
+                lines.push(restart_hint(cause, &state.stats).to_string());
+            }
+            if let Some(trial) = &state.trial {
+                lines.extend(record_splits(trial));
+            }
+            // Practice runs don't go on the board or count towards a career
+            if !state.settings.practice {
+                lines.extend(record_career(&state, &slot_path(state.settings.save_slot)));
+                lines.extend(record_high_score(&state));
+            }
+            let timeout = state.settings.game_over_timeout();
+            lines.push(closing_line(timeout));
+            renderer.present(&lines);
+            // Keep the summary up until a key (or the timeout) so leaving the
+            // alternate screen doesn't wipe it straight away
+            read_input(timeout).await;
+            break;
+        }
+
+        tokio::time::sleep(FRAME_DELAY).await;
+    }
+
+    // Restore the terminal first so any errors below are visible
+    drop(terminal);
+    // A saved run isn't over yet, so it's recorded when it finishes
+    if let Some(message) = saved {
+        eprintln!("{}", message);
+        return;
+    }
+    // Quitting mid-run still banks what was earned
+    if state.mode == GameMode::Playing && state.tick > 0 && !state.settings.practice {
+        for line in record_career(&state, &slot_path(state.settings.save_slot)) {
+            eprintln!("{}", line);
+        }
+    }
+    if state.tick > 0 {
+        // Perf mode records no path to make a ghost of
+        if state.settings.effects_enabled() {
+            save_ghost(&state);
+        }
+        if let Some(path) = &state.settings.stats_out {
+            if let Err(e) = state.summary().write(path) {
+                eprintln!("{}", e);
+            }
+        }
+        if state.settings.history {
+            if let Err(e) = record_history(&state.summary()) {
+                eprintln!("{}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    const TICK: Duration = Duration::from_millis(100);
+
+    // A fixed-seed game on `settings`, started straight away
+    fn game(settings: Settings) -> GameState {
+        GameState::new(Settings { skip_intro: true, seed: Some(7), ..settings }, Arena::default())
+    }
+
+    // A scratch file for this test alone, cleared of anything a previous run left
+    fn temp_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("rusty-space-miner-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn save_and_quit_resumes_where_the_run_left_off() {
+        let path = temp_file("save-and-quit.json");
+        let mut state = game(Settings::default());
+        state.asteroids.clear();
+        for _ in 0..5 {
+            state.step(&InputEvent::Down, TICK);
+        }
+        state.score = 120;
+        state.milestone_system();
+        assert_eq!(state.milestones_reached, 2);
+        save_run(&state, &path);
+
+        let run = SaveSlot::take_run(&path).unwrap().expect("run was saved");
+        let mut resumed = game(Settings::default());
+        resumed.resume(run);
+        assert_eq!(resumed.score, 120);
+        assert_eq!(resumed.tick, state.tick);
+        assert_eq!((resumed.ship.x, resumed.ship.y), (state.ship.x, state.ship.y));
+        assert_eq!(resumed.milestones_reached, 2);
+        assert_eq!(resumed.stats, state.stats);
+        assert_eq!(resumed.path, state.path);
+        assert_eq!(resumed.input_log, "sssss");
+        // Milestones already passed don't fire again
+        let resources = resumed.resources.len();
+        resumed.step(&InputEvent::None, TICK);
+        assert_eq!(resumed.milestones_reached, 2);
+        assert_eq!(resumed.resources.len(), resources);
+        // The run can only be resumed once
+        assert!(SaveSlot::take_run(&path).unwrap().is_none());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn only_single_ship_runs_can_be_saved() {
+        assert!(game(Settings::default()).can_save());
+        assert!(!game(Settings { two_player: true, ..Settings::default() }).can_save());
+        assert!(!game(Settings { level: LevelType::TimeTrial, ..Settings::default() }).can_save());
+        let mut state = game(Settings::default());
+        state.drone = Some(Drone::new(state.ship.x, state.ship.y));
+        assert!(!state.can_save());
+    }
+
+    #[test]
+    fn quit_without_saving_keeps_the_saved_run() {
+        let path = temp_file("quit-without-saving.json");
+        let earlier = game(Settings::default());
+        let slot = SaveSlot { credits: 5, runs: 1, run: Some(earlier.saved_run()) };
+        slot.save(&path).unwrap();
+
+        let mut state = game(Settings::default());
+        state.credits = 30;
+        state.step(&InputEvent::None, TICK);
+        // Quitting mid-run only banks the credits
+        record_career(&state, &path);
+        let slot = SaveSlot::load(&path).unwrap();
+        assert_eq!(slot.credits, 35);
+        let run = slot.run.expect("saved run kept");
+        assert_eq!(run.seed, earlier.seed);
+        assert_eq!(run.tick, 0);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn gold_takes_longer_to_mine_than_iron() {
+        assert!(mine_duration(Resource::Gold, &[]) > mine_duration(Resource::Iron, &[]));
+    }
+
+    #[test]
+    fn laser_shortens_mining() {
+        let lasered = [upgrades::find_upgrade("Laser", &[]).unwrap()];
+        assert!(mine_duration(Resource::Gold, &lasered) < mine_duration(Resource::Gold, &[]));
+        assert_eq!(mine_duration(Resource::Crystal, &lasered), 1);
+    }
+
+    #[test]
+    fn scrolling_level_mines_gold_as_it_passes() {
+        let mut state = game(Settings { level: LevelType::Scrolling, ..Settings::default() });
+        state.asteroids.clear();
+        let (x, y) = (state.ship.x, state.ship.y);
+        // Scrolls under the ship this tick and on past it the next
+        state.resources = vec![ResourceNode::new(x, y - 1, Resource::Gold, Duration::ZERO)];
+        state.step(&InputEvent::Mine, TICK);
+        assert_eq!(state.ship.cargo[&Resource::Gold], 1);
+    }
+
+    #[test]
+    fn scrolling_moves_everything_down_a_row() {
+        let mut asteroids = vec![Asteroid { x: 3, y: 0, vx: 0, vy: 0 }];
+        let mut resources = vec![ResourceNode::new(5, 4, Resource::Iron, Duration::ZERO)];
+        scroll_system(&mut asteroids, &mut resources, &PLAYFIELD, false);
+        assert_eq!((asteroids[0].x, asteroids[0].y), (3, 1));
+        assert_eq!((resources[0].x, resources[0].y), (5, 5));
+    }
+
+    #[test]
+    fn scrolling_drops_what_reaches_the_bottom() {
+        let bottom = PLAYFIELD.h - 1;
+        let mut asteroids = vec![Asteroid { x: 3, y: bottom, vx: 0, vy: 0 }, Asteroid { x: 4, y: bottom - 1, vx: 0, vy: 0 }];
+        let mut resources = vec![ResourceNode::new(5, bottom, Resource::Iron, Duration::ZERO)];
+        scroll_system(&mut asteroids, &mut resources, &PLAYFIELD, false);
+        assert_eq!(asteroids.len(), 1);
+        assert_eq!((asteroids[0].x, asteroids[0].y), (4, bottom));
+        assert!(resources.is_empty());
+    }
+
+    #[test]
+    fn scrolling_ship_stepping_into_an_asteroid_dies() {
+        let mut state = game(Settings { level: LevelType::Scrolling, ..Settings::default() });
+        let (x, y) = (state.ship.x, state.ship.y);
+        state.asteroids = vec![Asteroid { x: x + 1, y, vx: 0, vy: 0 }];
+        state.step(&InputEvent::Right, TICK);
+        assert_eq!(state.mode, GameMode::GameOver);
+        assert_eq!(state.death_cause, Some(DeathCause::Asteroid));
+    }
+
+    #[test]
+    fn perf_mode_records_no_trail() {
+        let mut state = game(Settings { perf: true, ..Settings::default() });
+        state.asteroids.clear();
+        for _ in 0..3 {
+            state.step(&InputEvent::Down, TICK);
+        }
+        assert_eq!(state.tick, 3);
+        assert_eq!(state.path.len(), 1);
+
+        let mut state = game(Settings::default());
+        state.asteroids.clear();
+        state.step(&InputEvent::Down, TICK);
+        assert_eq!(state.path.len(), 2);
+    }
+
+    #[test]
+    fn perf_mode_skips_early_redraws_only() {
+        let soon = Some(Duration::from_millis(10));
+        assert!(redraw_due(false, soon, true));
+        assert!(!redraw_due(true, soon, true));
+        assert!(redraw_due(true, Some(PERF_REDRAW_INTERVAL), true));
+        assert!(redraw_due(true, None, true));
+        // Waiting on a key with nothing queued, the screen has to be up to date
+        assert!(redraw_due(true, soon, false));
+    }
+
+    #[test]
+    fn ship_stops_with_its_whole_glyph_clear_of_a_wall() {
+        // Two cells past the right end of the glyph
+        let arena = Arena::parse("\n\n\n\n\n\n\n\n\n\n              #");
+        let mut ship = Ship::new();
+        for _ in 0..3 {
+            physics_system(&InputEvent::Right, &mut ship, &PLAYFIELD, &arena, 0.0);
+        }
+        assert_eq!((ship.x, ship.y), (11, 10));
+        assert!(glyph_cells(ship.x, ship.y).all(|(x, y)| !arena.is_wall(x, y)));
+        physics_system(&InputEvent::Left, &mut ship, &PLAYFIELD, &arena, 0.0);
+        assert_eq!((ship.x, ship.y), (10, 10));
+    }
+
+    #[test]
+    fn spawns_avoid_walls() {
+        // Walls everywhere but the top row
+        let wall_row = "#".repeat(PLAYFIELD.w as usize);
+        let rows: Vec<&str> = (0..PLAYFIELD.h).map(|y| if y == 0 { "" } else { wall_row.as_str() }).collect();
+        let mut state = GameState::new(Settings { skip_intro: true, seed: Some(7), ..Settings::default() }, Arena::parse(&rows.join("\n")));
+        for _ in 0..50 {
+            if let Some((x, y)) = state.asteroid_cell() {
+                assert!(!state.arena.is_wall(x, y));
+            }
+            if let Some((x, y)) = state.resource_cell() {
+                assert!(!state.arena.is_wall(x, y));
+            }
+        }
+        let sealed = vec![wall_row.as_str(); PLAYFIELD.h as usize].join("\n");
+        let mut state = GameState::new(Settings { skip_intro: true, seed: Some(7), ..Settings::default() }, Arena::parse(&sealed));
+        assert_eq!(state.random_cell(), None);
+        assert_eq!(state.asteroid_cell(), None);
+    }
+
+    #[test]
+    fn arena_walling_the_start_or_station_is_refused() {
+        assert!(check_arena(&Arena::parse("#")).is_ok());
+        let mut start = vec![String::new(); 10];
+        start.push("            #".to_string());
+        assert!(check_arena(&Arena::parse(&start.join("\n"))).is_err());
+        let station = format!("\n\n{}#", " ".repeat(STATION.0 as usize));
+        assert!(check_arena(&Arena::parse(&station)).is_err());
+    }
+
+    #[test]
+    fn two_directions_make_a_diagonal() {
+        let action = coalesce_inputs(&[InputEvent::Up, InputEvent::Right]);
+        assert_eq!(action.input(), InputEvent::UpRight);
+    }
+
+    #[test]
+    fn held_key_repeats_collapse_into_one_move() {
+        let action = coalesce_inputs(&[InputEvent::Right; 5]);
+        assert_eq!(action.movement, (1, 0));
+        assert_eq!(action.input(), InputEvent::Right);
+    }
+
+    #[test]
+    fn quit_wins_and_interrupt_beats_quit() {
+        let action = coalesce_inputs(&[InputEvent::Up, InputEvent::Pause, InputEvent::Quit, InputEvent::Mine]);
+        assert_eq!(action.input(), InputEvent::Quit);
+        let action = coalesce_inputs(&[InputEvent::Interrupt, InputEvent::Quit]);
+        assert_eq!(action.input(), InputEvent::Interrupt);
+    }
+
+    #[test]
+    fn ally_move_keeps_player_ones_move() {
+        let action = coalesce_inputs(&[InputEvent::Left, InputEvent::AllyDown, InputEvent::Up]);
+        assert_eq!(action.input(), InputEvent::UpLeft);
+        assert_eq!(action.ally_input(), InputEvent::AllyDown);
+
+        let mut state = game(Settings { two_player: true, ..Settings::default() });
+        state.asteroids.clear();
+        state.resources.clear();
+        let (ship, ally) = ((state.ship.x, state.ship.y), state.ally.as_ref().map(|a| (a.x, a.y)).unwrap());
+        state.step_with_ally(&action.input(), &action.ally_input(), TICK);
+        assert_eq!((state.ship.x, state.ship.y), (ship.0 - 1, ship.1 - 1));
+        let moved = state.ally.as_ref().unwrap();
+        assert_eq!((moved.x, moved.y), (ally.0, ally.1 + 1));
+        assert_eq!(state.input_log, "7k");
+    }
+
+    #[test]
+    fn skip_intro_starts_straight_in_play() {
+        assert_eq!(initial_mode(&Settings::default()), GameMode::Welcome);
+        assert_eq!(initial_mode(&Settings { skip_intro: true, ..Settings::default() }), GameMode::Playing);
+    }
+
+    #[test]
+    fn new_game_starts_fresh() {
+        let state = game(Settings::default());
+        assert_eq!(state.mode, GameMode::Playing);
+        assert_eq!((state.tick, state.score, state.elapsed), (0, 0, Duration::ZERO));
+        assert_eq!(state.ship.fuel, state.ship.max_fuel());
+        assert!(state.ally.is_none());
+    }
+
+    #[test]
+    fn step_advances_the_clock_only_while_playing() {
+        let mut state = game(Settings::default());
+        state.asteroids.clear();
+        state.step(&InputEvent::None, TICK);
+        assert_eq!((state.tick, state.elapsed), (1, TICK));
+        state.mode = GameMode::GameOver;
+        state.step(&InputEvent::None, TICK);
+        assert_eq!((state.tick, state.elapsed), (1, TICK));
+    }
+
+    #[test]
+    fn milestones_fire_once_each_in_order() {
+        let milestones = vec![
+            config::Milestone { score: 10, event: MilestoneEvent::DifficultyBump },
+            config::Milestone { score: 20, event: MilestoneEvent::DifficultyBump },
+        ];
+        let mut state = game(Settings { milestones, ..Settings::default() });
+        state.milestone_system();
+        assert_eq!(state.difficulty_bumps, 0);
+        state.score = 15;
+        state.milestone_system();
+        state.milestone_system();
+        assert_eq!((state.milestones_reached, state.difficulty_bumps), (1, 1));
+        state.score = 25;
+        state.milestone_system();
+        assert_eq!((state.milestones_reached, state.difficulty_bumps), (2, 2));
+    }
+
+    #[test]
+    fn supply_drop_adds_one_of_each_resource() {
+        let milestones = vec![config::Milestone { score: 10, event: MilestoneEvent::SupplyDrop }];
+        let mut state = game(Settings { milestones, ..Settings::default() });
+        state.resources.clear();
+        state.score = 10;
+        state.milestone_system();
+        let kinds: Vec<Resource> = state.resources.iter().map(|r| r.kind).collect();
+        assert_eq!(kinds.len(), 3);
+        for kind in [Resource::Iron, Resource::Crystal, Resource::Gold] {
+            assert!(kinds.contains(&kind));
+        }
+    }
+
+    #[test]
+    fn ctrl_c_is_an_interrupt_not_convert() {
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(InputEvent::from(ctrl_c), InputEvent::Interrupt);
+        let c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::NONE);
+        assert_eq!(InputEvent::from(c), InputEvent::Convert);
+    }
+
+    // The only test touching INTERRUPTED, so it can't leak into the others
+    #[tokio::test]
+    async fn interrupt_signal_ends_input_and_the_quit_prompt() {
+        INTERRUPTED.store(true, Ordering::SeqCst);
+        let input = read_input(None).await;
+        let settings = Settings::default();
+        let mut renderer = Renderer::new(Vec::new(), settings.hud_refresh);
+        let choice = confirm_quit(&mut renderer, &game(settings));
+        INTERRUPTED.store(false, Ordering::SeqCst);
+        assert_eq!(input, InputEvent::Interrupt);
+        assert!(matches!(choice, QuitChoice::Quit));
+    }
+
+    #[test]
+    fn welcome_preview_drifts_left_and_wraps() {
+        let asteroids = vec![Asteroid { x: 5, y: 3, vx: -1, vy: 0 }, Asteroid { x: 0, y: 4, vx: -1, vy: 0 }];
+        let mut preview = WelcomePreview { asteroids };
+        preview.advance();
+        assert_eq!((preview.asteroids[0].x, preview.asteroids[0].y), (4, 3));
+        assert_eq!((preview.asteroids[1].x, preview.asteroids[1].y), (FIELD_WIDTH - 1, 4));
+        let fresh = WelcomePreview::new();
+        assert!(fresh.asteroids.iter().all(|a| a.x < FIELD_WIDTH && a.y < FIELD_HEIGHT));
+    }
+
+    // Asteroids filling the top rows of the field, `share` of it in all
+    fn fill_field(state: &mut GameState, share: f32) {
+        let b = state.bounds;
+        let count = (b.w as f32 * b.h as f32 * share).ceil() as u16;
+        state.asteroids = (0..count).map(|i| Asteroid { x: b.x + i % b.w, y: b.y + i / b.w, vx: 0, vy: 0 }).collect();
+    }
+
+    #[test]
+    fn dense_field_pauses_spawning() {
+        let mut state = game(Settings { dense_field_threshold: 0.25, ..Settings::default() });
+        fill_field(&mut state, 0.2);
+        assert!(!state.field_is_dense());
+        assert!(state.can_spawn_asteroid());
+        fill_field(&mut state, 0.3);
+        assert!(state.field_is_dense());
+        assert!(!state.can_spawn_asteroid());
+        state.settings.halt_spawns_when_dense = false;
+        assert!(state.can_spawn_asteroid());
+    }
+
+    #[test]
+    fn overlapping_asteroids_count_once_towards_density() {
+        let bounds = Rect { x: 0, y: 0, w: 10, h: 10 };
+        let stacked = vec![Asteroid { x: 1, y: 1, vx: 0, vy: 0 }; 5];
+        assert_eq!(field_density(&stacked, &bounds), 0.01);
+    }
+
+    #[test]
+    fn nodes_expire_after_their_lifetime() {
+        let secs = Duration::from_secs;
+        let mut resources = vec![
+            ResourceNode::new(1, 1, Resource::Iron, secs(0)),
+            ResourceNode::new(2, 2, Resource::Gold, secs(5)),
+        ];
+        expiry_system(&mut resources, secs(9), secs(10));
+        assert_eq!(resources.len(), 2);
+        expiry_system(&mut resources, secs(10), secs(10));
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0].kind, Resource::Gold);
+        // A zero lifetime keeps nodes forever
+        expiry_system(&mut resources, secs(1000), Duration::ZERO);
+        assert_eq!(resources.len(), 1);
+    }
+
+    #[test]
+    fn nodes_fade_in_the_last_quarter_of_their_lifetime() {
+        let mut state = game(Settings { resource_lifetime_secs: 8.0, ..Settings::default() });
+        let node = ResourceNode::new(1, 1, Resource::Iron, Duration::ZERO);
+        state.elapsed = Duration::from_secs(5);
+        assert!(!state.resource_fading(&node));
+        state.elapsed = Duration::from_secs(6);
+        assert!(state.resource_fading(&node));
+    }
+
+    #[test]
+    fn starting_asteroids_follow_the_setting() {
+        assert_eq!(game(Settings::default()).asteroids.len(), 3);
+        assert_eq!(game(Settings { difficulty: config::Difficulty::Hard, ..Settings::default() }).asteroids.len(), 6);
+        assert_eq!(game(Settings { starting_asteroids: Some(10), ..Settings::default() }).asteroids.len(), 10);
+        assert!(game(Settings { starting_asteroids: Some(0), ..Settings::default() }).asteroids.is_empty());
+    }
+
+    #[test]
+    fn starting_asteroids_keep_clear_of_the_ship() {
+        let state = game(Settings { starting_asteroids: Some(30), ..Settings::default() });
+        let ship = &state.ship;
+        assert!(state.asteroids.iter().all(|a| a.x.abs_diff(ship.x) > SHIP_CLEARANCE || a.y.abs_diff(ship.y) > SHIP_CLEARANCE));
+        assert!(state.asteroids.iter().all(|a| (a.x, a.y) != STATION));
+    }
+
+    #[test]
+    fn tether_pumps_fuel_up_to_the_receivers_capacity() {
+        let mut from = Ship::new();
+        let mut to = Ship { fuel: 10.0, ..Ship::new() };
+        let moved = transfer_fuel(&mut from, &mut to, Duration::from_secs(1));
+        assert_eq!(moved, TETHER_RATE);
+        assert_eq!((from.fuel, to.fuel), (MAX_FUEL - TETHER_RATE, 10.0 + TETHER_RATE));
+        // A full tank takes nothing more
+        let mut full = Ship::new();
+        assert_eq!(transfer_fuel(&mut from, &mut full, Duration::from_secs(1)), 0.0);
+    }
+
+    #[test]
+    fn tether_only_works_between_adjacent_ships() {
+        let mut state = game(Settings { two_player: true, ..Settings::default() });
+        state.asteroids.clear();
+        let ally_x = state.ship.x + SHIP_WIDTH;
+        state.ally.as_mut().unwrap().fuel = 10.0;
+        state.step(&InputEvent::Tether, TICK);
+        // Five cells apart at the start, so nothing moves
+        assert!(state.ally.as_ref().unwrap().fuel < 10.0);
+        state.ally.as_mut().unwrap().x = ally_x;
+        let before = state.ally.as_ref().unwrap().fuel;
+        state.step(&InputEvent::Tether, TICK);
+        assert!(state.ally.as_ref().unwrap().fuel > before);
+    }
+
+    #[test]
+    fn tether_pumps_nothing_while_a_ship_is_moving() {
+        let mut state = game(Settings { two_player: true, ..Settings::default() });
+        state.asteroids.clear();
+        let ally = state.ally.as_mut().unwrap();
+        (ally.x, ally.fuel) = (state.ship.x + SHIP_WIDTH, 10.0);
+        state.step_with_ally(&InputEvent::Tether, &InputEvent::AllyUp, TICK);
+        let ally = state.ally.as_ref().unwrap();
+        assert!(ships_adjacent(&state.ship, ally));
+        assert!(ally.fuel < 10.0);
+    }
+
+    #[test]
+    fn stats_count_moves_mines_and_distance() {
+        let mut state = game(Settings::default());
+        state.asteroids.clear();
+        let (x, y) = (state.ship.x, state.ship.y);
+        state.resources = vec![ResourceNode::new(x + 1, y, Resource::Iron, Duration::ZERO)];
+        state.step(&InputEvent::Right, TICK);
+        state.step(&InputEvent::Mine, TICK);
+        state.step(&InputEvent::UpRight, TICK);
+        assert_eq!(state.stats, RunStats { moves: 2, mines: 1, distance: 3, spawn_failures: state.stats.spawn_failures });
+    }
+
+    #[test]
+    fn graze_ring_surrounds_the_kill_zone() {
+        let rock = [Asteroid { x: 10, y: 5, vx: 0, vy: 0 }];
+        let ship_at = |x, y| Ship { x, y, ..Ship::new() };
+        // The ship's tail cell overlaps the asteroid
+        assert_eq!(collision_system(&ship_at(8, 5), &rock, 1), Collision::Core(0));
+        // A cell further left its tail is next to the asteroid
+        assert_eq!(collision_system(&ship_at(7, 5), &rock, 1), Collision::Graze(1));
+        assert_eq!(collision_system(&ship_at(7, 5), &rock, 0), Collision::None);
+        assert_eq!(collision_system(&ship_at(10, 7), &rock, 1), Collision::None);
+    }
+
+    #[test]
+    fn grazing_wears_the_hull_down_until_it_breaches() {
+        let mut state = game(Settings { graze_radius: 1, graze_damage: 40.0, ..Settings::default() });
+        let (x, y) = (state.ship.x, state.ship.y);
+        state.asteroids = vec![Asteroid { x: x + SHIP_WIDTH, y: y + 1, vx: 0, vy: 0 }];
+        state.step(&InputEvent::None, TICK);
+        assert_eq!(state.mode, GameMode::Playing);
+        assert_eq!(state.ship.hull, MAX_HULL - 40.0);
+        state.step(&InputEvent::None, TICK);
+        state.step(&InputEvent::None, TICK);
+        assert_eq!(state.mode, GameMode::GameOver);
+        assert_eq!(state.death_cause, Some(DeathCause::HullBreached));
+    }
+
+    #[test]
+    fn hiding_the_hud_hands_its_rows_to_the_playfield() {
+        let mut state = game(Settings::default());
+        state.toggle_hud();
+        assert!(!state.hud_visible);
+        assert_eq!(state.bounds.h, PLAYFIELD.h + HUD_ROWS);
+        state.ship.y = state.bounds.h - 1;
+        state.resources.push(ResourceNode::new(2, state.bounds.h - 1, Resource::Iron, Duration::ZERO));
+        state.toggle_hud();
+        assert!(state.hud_visible);
+        assert_eq!(state.bounds.h, PLAYFIELD.h);
+        // Anything in the rows taken back is pulled onto the smaller field
+        assert_eq!(state.ship.y, PLAYFIELD.h - 1);
+        assert!(state.resources.iter().all(|r| r.y < PLAYFIELD.h));
+    }
+
+    #[test]
+    fn hud_toggle_leaves_a_big_world_alone() {
+        let mut state = game(Settings { world_size: Some((80, 40)), ..Settings::default() });
+        state.toggle_hud();
+        assert_eq!(state.bounds.h, 40);
+        assert_eq!(state.camera.h, PLAYFIELD.h + HUD_ROWS);
+    }
+
+    #[test]
+    fn same_seed_and_inputs_retrace_the_same_path() {
+        let run = || {
+            let mut state = game(Settings::default());
+            for input in [InputEvent::Right, InputEvent::Down, InputEvent::Down, InputEvent::Left] {
+                state.step(&input, TICK);
+            }
+            state.path
+        };
+        let path = run();
+        assert_eq!(path.len(), 5);
+        assert_eq!(path, run());
+    }
+
+    #[test]
+    fn practice_has_no_asteroids_and_restocks_resources() {
+        let mut state = game(Settings { practice: true, ..Settings::default() });
+        assert!(state.asteroids.is_empty());
+        let (x, y) = (state.ship.x, state.ship.y);
+        state.resources = vec![ResourceNode::new(x, y, Resource::Iron, Duration::ZERO)];
+        state.step(&InputEvent::Mine, TICK);
+        assert_eq!(state.ship.cargo[&Resource::Iron], 1);
+        assert_eq!(state.resources.len(), PRACTICE_NODES);
+        for _ in 0..100 {
+            state.step(&InputEvent::None, TICK);
+        }
+        assert_eq!(state.mode, GameMode::Playing);
+        assert!(state.asteroids.is_empty());
+    }
+
+    #[test]
+    fn summary_records_how_the_run_ended() {
+        let mut state = game(Settings::default());
+        let (x, y) = (state.ship.x, state.ship.y);
+        state.asteroids = vec![Asteroid { x: x + SHIP_WIDTH, y, vx: 0, vy: 0 }];
+        state.step(&InputEvent::Right, TICK);
+        let summary = state.summary();
+        assert_eq!(summary.seed, 7);
+        assert_eq!(summary.death_cause, Some(DeathCause::Asteroid));
+        assert_eq!(summary.duration_secs, TICK.as_secs_f64());
+        assert!(!summary.victory);
+    }
+
+    fn magnet_ship() -> Ship {
+        Ship { x: 10, y: 5, upgrades: vec![upgrades::find_upgrade("Magnet", &[]).unwrap()], ..Ship::new() }
+    }
+
+    #[test]
+    fn magnet_pull_falls_off_with_distance() {
+        let ship = magnet_ship();
+        let pull = |x| magnet_pull(&ship, &ResourceNode::new(x, 5, Resource::Iron, Duration::ZERO), 2.0).0;
+        assert_eq!(pull(8), 1.0);
+        assert_eq!(pull(6), 0.5);
+        assert!(pull(5) < pull(6));
+        assert_eq!(pull(3), 0.0);
+        assert_eq!(pull(10), 0.0);
+    }
+
+    #[test]
+    fn magnet_drags_nodes_in_but_not_onto_asteroids() {
+        let ship = magnet_ship();
+        let mut resources = vec![ResourceNode::new(7, 5, Resource::Iron, Duration::ZERO)];
+        magnet_system(&ship, &mut resources, &[], &Arena::default(), 3.0);
+        assert_eq!((resources[0].x, resources[0].y), (8, 5));
+
+        let asteroids = [Asteroid { x: 9, y: 5, vx: 0, vy: 0 }];
+        magnet_system(&ship, &mut resources, &asteroids, &Arena::default(), 3.0);
+        assert_eq!((resources[0].x, resources[0].y), (8, 5));
+        // Without the magnet nothing moves
+        let mut resources = vec![ResourceNode::new(7, 5, Resource::Iron, Duration::ZERO)];
+        magnet_system(&Ship { upgrades: Vec::new(), ..ship }, &mut resources, &[], &Arena::default(), 3.0);
+        assert_eq!(resources[0].x, 7);
+    }
+
+    #[test]
+    fn game_over_screen_says_when_it_closes() {
+        assert_eq!(closing_line(None), "Press any key to exit");
+        assert_eq!(closing_line(Some(Duration::from_millis(2500))), "Closing in 3s, or press any key");
+    }
+
+    #[test]
+    fn difficulty_ramps_with_game_time_not_frames() {
+        let ramp = |ticks: u32, dt: Duration| {
+            let mut state = game(Settings { practice: true, difficulty_ramp_secs: 10.0, ..Settings::default() });
+            state.ship.fuel = f32::MAX;
+            for _ in 0..ticks {
+                state.step(&InputEvent::None, dt);
+            }
+            state.difficulty().ramp_steps
+        };
+        assert_eq!(ramp(10, Duration::from_secs(3)), 3);
+        assert_eq!(ramp(300, TICK), 3);
+    }
+
+    #[test]
+    fn scrolling_holds_asteroids_still_during_resource_rain() {
+        let mut state = game(Settings { level: LevelType::Scrolling, ..Settings::default() });
+        state.active_event = Some(ActiveEvent::ResourceRain { ends_at: Duration::from_secs(60) });
+        state.asteroids = vec![Asteroid { x: 3, y: 2, vx: 0, vy: 0 }];
+        state.resources = vec![ResourceNode::new(5, 4, Resource::Iron, Duration::ZERO)];
+        state.step(&InputEvent::None, TICK);
+        assert_eq!((state.asteroids[0].x, state.asteroids[0].y), (3, 2));
+        assert_eq!((state.resources[0].x, state.resources[0].y), (5, 5));
+    }
+
+    #[test]
+    fn resource_rain_holds_asteroids_and_showers_nodes_until_it_ends() {
+        let mut state = game(Settings::default());
+        state.active_event = Some(ActiveEvent::ResourceRain { ends_at: state.elapsed + 3 * TICK });
+        state.asteroids = vec![Asteroid { x: 1, y: 1, vx: 1, vy: 0 }];
+        state.resources.clear();
+        for _ in 0..2 {
+            state.step(&InputEvent::None, TICK);
+        }
+        assert_eq!((state.asteroids[0].x, state.asteroids[0].y), (1, 1));
+        assert_eq!(state.resources.len(), 2);
+        // Time's up: the next tick ends the rain and the field moves again
+        state.step(&InputEvent::None, TICK);
+        assert!(state.active_event.is_none());
+        assert_eq!((state.asteroids[0].x, state.asteroids[0].y), (2, 1));
+    }
+
+    #[test]
+    fn fuel_drain_climbs_over_a_hard_run_and_stays_flat_on_easy() {
+        let burned = |difficulty, elapsed| {
+            let mut state = game(Settings { difficulty, ..Settings::default() });
+            state.asteroids.clear();
+            state.elapsed = elapsed;
+            let before = state.ship.total_fuel();
+            state.step(&InputEvent::None, TICK);
+            before - state.ship.total_fuel()
+        };
+        let five_minutes = Duration::from_secs(300);
+        assert!(burned(config::Difficulty::Hard, five_minutes) > burned(config::Difficulty::Hard, Duration::ZERO));
+        assert_eq!(burned(config::Difficulty::Easy, five_minutes), burned(config::Difficulty::Easy, Duration::ZERO));
+    }
+
+    #[test]
+    fn full_hold_stops_mining() {
+        let mut state = game(Settings { cargo_capacity: Some(1), ..Settings::default() });
+        state.asteroids.clear();
+        let (x, y) = (state.ship.x, state.ship.y);
+        state.resources = vec![ResourceNode::new(x, y, Resource::Iron, Duration::ZERO).rich(2)];
+        state.step(&InputEvent::Mine, TICK);
+        assert_eq!(state.ship.cargo_room(), 0);
+        state.step(&InputEvent::Mine, TICK);
+        assert_eq!(state.ship.cargo[&Resource::Iron], 1);
+        assert_eq!(state.resources[0].amount, 1);
+    }
+
+    #[test]
+    fn auto_mine_collects_a_node_by_flying_onto_it() {
+        let flown = |auto_mine| {
+            let mut state = game(Settings { auto_mine, ..Settings::default() });
+            state.asteroids.clear();
+            let (x, y) = (state.ship.x, state.ship.y);
+            state.resources = vec![ResourceNode::new(x + 1, y, Resource::Iron, Duration::ZERO)];
+            state.step(&InputEvent::Right, TICK);
+            state.ship.cargo[&Resource::Iron]
+        };
+        assert_eq!(flown(true), 1);
+        assert_eq!(flown(false), 0);
+    }
+
+    #[test]
+    fn meeting_the_goal_wins_and_falling_short_plays_on() {
+        let mut state = game(Settings { win_condition: Some(WinCondition::Score(100)), ..Settings::default() });
+        state.asteroids.clear();
+        state.score = 90;
+        state.step(&InputEvent::None, TICK);
+        assert_eq!(state.mode, GameMode::Playing);
+        state.score = 100;
+        state.step(&InputEvent::None, TICK);
+        assert_eq!(state.mode, GameMode::Victory);
+
+        let goal = WinCondition::Collect { resource: Resource::Gold, count: 2 };
+        let mut state = game(Settings { win_condition: Some(goal), ..Settings::default() });
+        state.asteroids.clear();
+        state.ship.cargo.insert(Resource::Gold, 1);
+        state.step(&InputEvent::None, TICK);
+        assert_eq!(state.mode, GameMode::Playing);
+        state.ship.cargo.insert(Resource::Gold, 2);
+        state.step(&InputEvent::None, TICK);
+        assert_eq!(state.mode, GameMode::Victory);
+    }
+
+    #[test]
+    fn surviving_long_enough_wins() {
+        let mut state = game(Settings { win_condition: Some(WinCondition::SurviveSecs(0.25)), ..Settings::default() });
+        state.asteroids.clear();
+        state.step(&InputEvent::None, TICK);
+        state.step(&InputEvent::None, TICK);
+        assert_eq!(state.mode, GameMode::Playing);
+        state.step(&InputEvent::None, TICK);
+        assert_eq!(state.mode, GameMode::Victory);
+    }
+
+    #[test]
+    fn converting_cargo_deducts_it_and_tops_up_the_tank() {
+        let mut ship = Ship::new();
+        ship.cargo.insert(Resource::Iron, 3);
+        ship.fuel = 50.0;
+        assert_eq!(convert_to_fuel(&mut ship, Resource::Iron, 2, 10.0), Ok(20.0));
+        assert_eq!(ship.cargo[&Resource::Iron], 1);
+        assert_eq!(ship.fuel, 70.0);
+        // Only what fits in the tank counts
+        ship.fuel = 95.0;
+        assert_eq!(convert_to_fuel(&mut ship, Resource::Iron, 1, 10.0), Ok(5.0));
+        assert_eq!(ship.fuel, MAX_FUEL);
+    }
+
+    #[test]
+    fn converting_more_cargo_than_held_is_refused() {
+        let mut ship = Ship::new();
+        ship.cargo.insert(Resource::Gold, 1);
+        ship.fuel = 50.0;
+        assert!(convert_to_fuel(&mut ship, Resource::Gold, 2, 10.0).is_err());
+        assert_eq!(ship.cargo[&Resource::Gold], 1);
+        assert_eq!(ship.fuel, 50.0);
+    }
+
+    #[test]
+    fn paused_game_stands_still() {
+        let mut state = game(Settings::default());
+        state.paused = true;
+        let (x, y) = (state.ship.x, state.ship.y);
+        state.step(&InputEvent::Right, TICK);
+        assert_eq!(state.tick, 0);
+        assert_eq!(state.elapsed, Duration::ZERO);
+        assert_eq!((state.ship.x, state.ship.y), (x, y));
+    }
+
+    #[test]
+    fn hint_fits_how_the_run_went() {
+        let idle = RunStats::default();
+        let busy = RunStats { moves: 40, mines: 3, distance: 30, spawn_failures: 0 };
+        assert_eq!(restart_hint(DeathCause::OutOfFuel, &idle), "Tip: mine Crystal to top up your fuel");
+        assert_eq!(restart_hint(DeathCause::OutOfFuel, &busy), "Tip: dock at the station and press C to turn cargo into fuel");
+        assert_eq!(restart_hint(DeathCause::Asteroid, &idle), "Tip: keep moving, new asteroids can appear anywhere");
+        assert_eq!(restart_hint(DeathCause::Asteroid, &busy), "Tip: plan a route around asteroids before heading for a resource");
+        assert_eq!(restart_hint(DeathCause::HullBreached, &busy), "Tip: grazing asteroids wears down the hull, give them a wider berth");
+    }
+
+    #[test]
+    fn fuel_warning_levels_follow_the_reserve() {
+        assert_eq!(fuel_state(80.0, 20.0), FuelState::Good);
+        assert_eq!(fuel_state(LOW_FUEL, 20.0), FuelState::Low);
+        assert_eq!(fuel_state(20.5, 20.0), FuelState::Low);
+        assert_eq!(fuel_state(20.0, 20.0), FuelState::Critical);
+        // A reserve above the usual low mark warns from the reserve up
+        assert_eq!(fuel_state(70.0, 60.0), FuelState::Good);
+        assert_eq!(fuel_state(55.0, 60.0), FuelState::Critical);
+    }
+
+    // Widest gap across a set of asteroids, in cells
+    fn spread(asteroids: &[Asteroid]) -> u16 {
+        let span = |cells: Vec<u16>| cells.iter().max().unwrap() - cells.iter().min().unwrap();
+        span(asteroids.iter().map(|a| a.x).collect()).max(span(asteroids.iter().map(|a| a.y).collect()))
+    }
+
+    #[test]
+    fn clustered_spawns_stay_tight_and_in_bounds() {
+        let mut state = game(Settings { asteroid_spawn: SpawnPattern::Clustered, ..Settings::default() });
+        for _ in 0..20 {
+            state.asteroids.clear();
+            state.spawn_cluster();
+            if state.asteroids.is_empty() {
+                continue;
+            }
+            assert!(spread(&state.asteroids) <= 2 * CLUSTER_RADIUS);
+            assert!(state.asteroids.iter().all(|a| geometry::in_bounds(a.x as i32, a.y as i32, &state.bounds)));
+        }
+    }
+
+    #[test]
+    fn uniform_spawns_spread_across_the_field() {
+        let mut state = game(Settings::default());
+        state.asteroids.clear();
+        for _ in 0..20 {
+            let (x, y) = state.asteroid_cell().unwrap();
+            state.asteroids.push(Asteroid { x, y, vx: 0, vy: 0 });
+        }
+        assert!(spread(&state.asteroids) > 2 * CLUSTER_RADIUS);
+        assert!(state.asteroids.iter().all(|a| geometry::in_bounds(a.x as i32, a.y as i32, &state.bounds)));
+    }
+
+    #[test]
+    fn yields_are_exact_without_variance_and_banded_with_it() {
+        let mut rng = StdRng::seed_from_u64(7);
+        assert!((0..100).all(|_| mining_yield(&mut rng, 0) == 1));
+        let yields: Vec<u32> = (0..200).map(|_| mining_yield(&mut rng, 2)).collect();
+        assert!(yields.iter().all(|y| (1..=3).contains(y)));
+        assert!(yields.contains(&1) && yields.contains(&3));
+    }
+
+    #[test]
+    fn each_resource_gives_its_configured_fuel() {
+        let values = HashMap::from([(Resource::Iron, 5.0), (Resource::Crystal, 20.0), (Resource::Gold, 12.0)]);
+        let fuel_after_mining = |fuel_values: HashMap<Resource, f32>, kind: Resource| {
+            // Easy leaves fuel values unscaled
+            let mut state = game(Settings { fuel_values, difficulty: config::Difficulty::Easy, ..Settings::default() });
+            state.asteroids.clear();
+            state.ship.fuel = 50.0;
+            let (x, y) = (state.ship.x, state.ship.y);
+            state.resources = vec![ResourceNode::new(x, y, kind, Duration::ZERO)];
+            for _ in 0..kind.hardness() {
+                state.step(&InputEvent::Mine, TICK);
+            }
+            assert_eq!(state.ship.cargo[&kind], 1);
+            state.ship.fuel
+        };
+        for (&kind, &value) in &values {
+            let gained = fuel_after_mining(values.clone(), kind) - fuel_after_mining(HashMap::new(), kind);
+            assert!((gained - value).abs() < 1e-4, "{:?} gave {}", kind, gained);
+        }
+    }
+
+    #[test]
+    fn camera_holds_still_inside_the_deadzone() {
+        let world = Rect { x: 0, y: 0, w: 100, h: 50 };
+        let cam = Rect { x: 20, y: 10, w: 32, h: 15 };
+        // The (12, 5) deadzone spans x 30..=41 and y 15..=19 of this view
+        for (x, y) in [(30, 15), (41, 19), (35, 17)] {
+            let ship = Ship { x, y, ..Ship::new() };
+            assert_eq!(camera_follow(cam, &ship, (12, 5), &world), cam);
+        }
+    }
+
+    #[test]
+    fn camera_scrolls_once_the_ship_leaves_the_deadzone() {
+        let world = Rect { x: 0, y: 0, w: 100, h: 50 };
+        let cam = Rect { x: 20, y: 10, w: 32, h: 15 };
+        let follow = |x, y| camera_follow(cam, &Ship { x, y, ..Ship::new() }, (12, 5), &world);
+        assert_eq!((follow(44, 17).x, follow(44, 17).y), (23, 10));
+        assert_eq!((follow(35, 13).x, follow(35, 13).y), (20, 8));
+        // Never past the edges of the world
+        assert_eq!(follow(99, 49), Rect { x: 68, y: 35, ..cam });
+        assert_eq!(follow(0, 0), Rect { x: 0, y: 0, ..cam });
+    }
+
+    #[test]
+    fn hardcore_death_deletes_the_slot_and_normal_mode_keeps_it() {
+        let banked = |hardcore, name| {
+            let path = temp_file(name);
+            SaveSlot { credits: 50, runs: 1, run: None }.save(&path).unwrap();
+            let mut state = game(Settings { hardcore, ..Settings::default() });
+            state.credits = 10;
+            state.death_cause = Some(DeathCause::Asteroid);
+            record_career(&state, &path);
+            let kept = path.exists().then(|| SaveSlot::load(&path).unwrap().credits);
+            let _ = std::fs::remove_file(&path);
+            kept
+        };
+        assert_eq!(banked(true, "hardcore-slot.json"), None);
+        assert_eq!(banked(false, "normal-slot.json"), Some(60));
+    }
+
+    #[test]
+    fn moves_are_ignored_during_the_mine_dead_time_only() {
+        let mut dead_time = MineDeadTime::new(Duration::from_millis(150));
+        let mined_at = Instant::now();
+        assert!(!dead_time.suppresses(&InputEvent::Left, &InputEvent::None, mined_at));
+        dead_time.mined(mined_at);
+        let soon = mined_at + Duration::from_millis(100);
+        assert!(dead_time.suppresses(&InputEvent::Left, &InputEvent::None, soon));
+        assert!(dead_time.suppresses(&InputEvent::None, &InputEvent::AllyUp, soon));
+        // Mining again or any other key still goes through
+        assert!(!dead_time.suppresses(&InputEvent::Mine, &InputEvent::None, soon));
+        assert!(!dead_time.suppresses(&InputEvent::Left, &InputEvent::None, mined_at + Duration::from_millis(150)));
+    }
+
+    #[test]
+    fn run_ends_with_time_up_at_the_tick_cap() {
+        let mut state = game(Settings { max_ticks: Some(3), ..Settings::default() });
+        state.asteroids.clear();
+        state.step(&InputEvent::None, TICK);
+        state.step(&InputEvent::None, TICK);
+        assert_eq!(state.mode, GameMode::Playing);
+        state.step(&InputEvent::None, TICK);
+        assert_eq!(state.mode, GameMode::TimeUp);
+        assert_eq!(state.tick, 3);
+        assert!(state.death_cause.is_none());
+    }
+
+    #[test]
+    fn run_ends_with_time_up_at_the_seconds_cap() {
+        let mut state = game(Settings { max_seconds: Some(0.25), ..Settings::default() });
+        state.asteroids.clear();
+        state.step(&InputEvent::None, TICK);
+        state.step(&InputEvent::None, TICK);
+        assert_eq!(state.mode, GameMode::Playing);
+        state.step(&InputEvent::None, TICK);
+        assert_eq!(state.mode, GameMode::TimeUp);
+    }
+
+    #[test]
+    fn configured_upgrade_applies_every_effect() {
+        let bulwark = UpgradeDef { name: "Bulwark".to_string(), effects: vec![Effect::Shields, Effect::FuelCapacity(50.0)] };
+        let mut state = game(Settings {
+            upgrades: vec![bulwark],
+            starting_upgrades: vec!["Bulwark".to_string()],
+            ..Settings::default()
+        });
+        assert_eq!(state.ship.max_fuel(), MAX_FUEL + 50.0);
+        assert_eq!(state.ship.fuel, MAX_FUEL + 50.0);
+        assert_eq!(state.ship.shield_charges, 1);
+
+        // The shield takes the first strike and the asteroid breaks up on it
+        let (x, y) = (state.ship.x, state.ship.y);
+        state.asteroids = vec![Asteroid { x, y, vx: 0, vy: 0 }];
+        state.step(&InputEvent::None, TICK);
+        assert_eq!(state.mode, GameMode::Playing);
+        assert!(state.asteroids.is_empty());
+        assert_eq!(state.ship.shield_charges, 0);
+        state.asteroids = vec![Asteroid { x, y, vx: 0, vy: 0 }];
+        state.step(&InputEvent::None, TICK);
+        assert_eq!(state.death_cause, Some(DeathCause::Asteroid));
+    }
+
+    #[test]
+    fn knockback_throws_the_ship_clear_in_open_space() {
+        let mut ship = Ship { x: 10, y: 5, ..Ship::new() };
+        assert_eq!(knockback(&mut ship, (0, 1), 3, &PLAYFIELD, &Arena::default(), &[]), 3);
+        assert_eq!((ship.x, ship.y), (10, 8));
+    }
+
+    #[test]
+    fn knockback_is_cut_short_by_walls_asteroids_and_edges() {
+        // A wall two rows down stops the ship a row short of it, even one
+        // under the far end of the glyph
+        let arena = Arena::parse("\n\n\n\n\n\n\n            #");
+        let mut ship = Ship { x: 10, y: 5, ..Ship::new() };
+        assert_eq!(knockback(&mut ship, (0, 1), 3, &PLAYFIELD, &arena, &[]), 1);
+        assert_eq!((ship.x, ship.y), (10, 6));
+        // So does an asteroid under any cell of the glyph
+        let rock = [Asteroid { x: 12, y: 7, vx: 0, vy: 0 }];
+        let mut ship = Ship { x: 10, y: 5, ..Ship::new() };
+        assert_eq!(knockback(&mut ship, (0, 1), 3, &PLAYFIELD, &Arena::default(), &rock), 1);
+        assert_eq!((ship.x, ship.y), (10, 6));
+        // And the edge of the screen
+        let mut ship = Ship { x: 10, y: PLAYFIELD.h - 2, ..Ship::new() };
+        assert_eq!(knockback(&mut ship, (0, 1), 3, &PLAYFIELD, &Arena::default(), &[]), 1);
+        assert_eq!(ship.y, PLAYFIELD.h - 1);
+    }
+
+    #[test]
+    fn asteroid_spawns_keep_their_distance_from_the_ships() {
+        let mut state = game(Settings { min_spawn_distance: 6, ..Settings::default() });
+        for _ in 0..50 {
+            let (x, y) = state.asteroid_cell().unwrap();
+            assert!(x.abs_diff(state.ship.x).max(y.abs_diff(state.ship.y)) >= 6);
+        }
+    }
+
+    #[test]
+    fn asteroid_spawn_is_skipped_when_no_cell_is_far_enough() {
+        // Further than the field is wide, so nowhere qualifies
+        let mut state = game(Settings { min_spawn_distance: 100, ..Settings::default() });
+        state.asteroids.clear();
+        assert_eq!(state.asteroid_cell(), None);
+        for _ in 0..100 {
+            state.step(&InputEvent::None, TICK);
+        }
+        assert!(state.asteroids.is_empty());
+    }
+
+    #[test]
+    fn resource_spawns_land_on_free_cells() {
+        let mut state = game(Settings::default());
+        fill_field(&mut state, 0.5);
+        state.resources.clear();
+        for _ in 0..30 {
+            let (x, y) = state.resource_cell().unwrap();
+            assert!(!state.asteroids.iter().any(|a| (a.x, a.y) == (x, y)));
+            assert!(!state.resources.iter().any(|r| (r.x, r.y) == (x, y)));
+            assert_ne!((x, y), STATION);
+            state.resources.push(ResourceNode::new(x, y, Resource::Iron, Duration::ZERO));
+        }
+        assert_eq!(state.stats.spawn_failures, 0);
+    }
+
+    #[test]
+    fn full_field_skips_the_resource_spawn_and_counts_it() {
+        let mut state = game(Settings::default());
+        fill_field(&mut state, 1.0);
+        assert_eq!(state.resource_cell(), None);
+        assert_eq!(state.resource_cell(), None);
+        assert_eq!(state.stats.spawn_failures, 2);
+    }
+
+    #[test]
+    fn boss_arrives_only_once_its_warning_runs_out() {
+        let boss = config::Milestone { score: 10, event: config::MilestoneEvent::Boss };
+        let mut state = game(Settings { milestones: vec![boss], boss_warning_secs: 0.25, ..Settings::default() });
+        state.asteroids.clear();
+        state.score = 10;
+        // Warned on the first tick, at 0.1s, until 0.35s
+        for _ in 0..3 {
+            state.step(&InputEvent::None, TICK);
+            assert!(state.boss_warning.is_some());
+            assert!(state.asteroids.is_empty());
+        }
+        state.step(&InputEvent::None, TICK);
+        assert!(state.boss_warning.is_none());
+        assert_eq!(state.asteroids.len() as u16, BOSS_SIZE * BOSS_SIZE);
+    }
+
+    fn tanked_ship() -> Ship {
+        Ship {
+            fuel: 10.0,
+            reserve_fuel: 30.0,
+            tanks: FuelTanks { main: 100.0, reserve: 30.0, reserve_refill_rate: 0.5 },
+            ..Ship::new()
+        }
+    }
+
+    #[test]
+    fn main_tank_drains_before_the_reserve() {
+        let mut ship = tanked_ship();
+        ship.burn_fuel(4.0);
+        assert_eq!((ship.fuel, ship.reserve_fuel), (6.0, 30.0));
+        ship.burn_fuel(10.0);
+        assert_eq!((ship.fuel, ship.reserve_fuel), (0.0, 26.0));
+        ship.burn_fuel(50.0);
+        assert_eq!((ship.fuel, ship.reserve_fuel), (0.0, 0.0));
+    }
+
+    #[test]
+    fn refuel_fills_the_main_tank_then_the_reserve_at_its_rate() {
+        let mut ship = tanked_ship();
+        ship.reserve_fuel = 0.0;
+        assert_eq!(ship.refuel(50.0), 50.0);
+        assert_eq!((ship.fuel, ship.reserve_fuel), (60.0, 0.0));
+        // 40 fills the main tank; the other 20 overflows into the reserve at half rate
+        assert_eq!(ship.refuel(60.0), 50.0);
+        assert_eq!((ship.fuel, ship.reserve_fuel), (100.0, 10.0));
+        // Once both are full the rest is lost
+        ship.refuel(1000.0);
+        assert_eq!((ship.fuel, ship.reserve_fuel), (100.0, 30.0));
+    }
+
+    #[test]
+    fn one_button_turns_in_open_space_and_mines_on_a_node() {
+        let mut state = game(Settings { one_button: true, ..Settings::default() });
+        state.resources.clear();
+        state.heading = InputEvent::Up;
+        // Unpressed, the ship keeps flying its heading; pressed, it turns clockwise
+        assert_eq!(state.one_button_input(false), InputEvent::Up);
+        assert_eq!(state.one_button_input(true), InputEvent::Right);
+        assert_eq!(state.one_button_input(true), InputEvent::Down);
+        assert_eq!(state.one_button_input(true), InputEvent::Left);
+        assert_eq!(state.one_button_input(true), InputEvent::Up);
+
+        let (x, y) = (state.ship.x, state.ship.y);
+        state.resources = vec![ResourceNode::new(x, y, Resource::Iron, Duration::ZERO)];
+        assert_eq!(state.one_button_input(false), InputEvent::None);
+        assert_eq!(state.one_button_input(true), InputEvent::Mine);
+        assert_eq!(state.heading, InputEvent::Up);
+    }
+
+    #[test]
+    fn one_button_trades_at_the_station_and_steers_sideways_when_scrolling() {
+        let mut state = game(Settings { one_button: true, ..Settings::default() });
+        state.resources.clear();
+        (state.ship.x, state.ship.y) = STATION;
+        // With an empty hold there's nothing to trade, so the button turns
+        state.heading = InputEvent::Up;
+        assert_eq!(state.one_button_input(true), InputEvent::Right);
+        state.ship.cargo.insert(Resource::Iron, 1);
+        assert_eq!(state.one_button_input(true), InputEvent::Convert);
+
+        let mut state = game(Settings { one_button: true, level: LevelType::Scrolling, ..Settings::default() });
+        state.resources.clear();
+        let turns: Vec<_> = (0..3).map(|_| state.one_button_input(true)).collect();
+        assert!(turns.iter().all(|t| matches!(t, InputEvent::Left | InputEvent::Right)));
+        assert_ne!(turns[0], turns[1]);
+        assert_eq!(turns[0], turns[2]);
+    }
+
+    #[test]
+    fn crystal_fuel_shrinks_with_difficulty() {
+        let crystal_fuel = |difficulty| {
+            let state = game(Settings { difficulty, ..Settings::default() });
+            state.settings.fuel_value(Resource::Crystal, state.difficulty().fuel_value_scale)
+        };
+        assert_eq!(crystal_fuel(config::Difficulty::Easy), 20.0);
+        assert!((crystal_fuel(config::Difficulty::Normal) - 17.0).abs() < 1e-4);
+        assert!((crystal_fuel(config::Difficulty::Hard) - 14.0).abs() < 1e-4);
+
+        // And that's what mining one puts in the tank
+        let mut state = game(Settings { difficulty: config::Difficulty::Hard, ..Settings::default() });
+        state.asteroids.clear();
+        state.ship.fuel = 50.0;
+        let (x, y) = (state.ship.x, state.ship.y);
+        state.resources = vec![ResourceNode::new(x, y, Resource::Crystal, Duration::ZERO)];
+        state.step(&InputEvent::Mine, TICK);
+        let before = state.ship.fuel;
+        state.step(&InputEvent::Mine, TICK);
+        assert_eq!(state.ship.cargo[&Resource::Crystal], 1);
+        let drain = state.difficulty().fuel_drain;
+        assert!((state.ship.fuel - (before - drain + 14.0)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn mining_a_contaminated_node_costs_fuel() {
+        let mined = |contaminated| {
+            let mut state = game(Settings::default());
+            state.asteroids.clear();
+            state.ship.fuel = 50.0;
+            let (x, y) = (state.ship.x, state.ship.y);
+            let node = ResourceNode { contaminated, ..ResourceNode::new(x, y, Resource::Iron, Duration::ZERO) };
+            state.resources = vec![node];
+            state.step(&InputEvent::Mine, TICK);
+            assert_eq!(state.ship.cargo[&Resource::Iron], 1);
+            state.ship.fuel
+        };
+        assert!((mined(false) - mined(true) - CONTAMINATION_FUEL_LOSS).abs() < 1e-4);
+    }
+}
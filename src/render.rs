@@ -0,0 +1,1110 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::time::Duration;
+use crossterm::{
+    cursor,
+    queue,
+    style::{Color, Print, Stylize},
+    terminal::{self, ClearType},
+};
+use crate::{asteroid_zones, Edge, fuel_state, ship_hitbox, Asteroid, FuelState, GameState, Rect, Resource, ResourceNode, Ship, MAX_HULL, STATION};
+use crate::arena::Arena;
+use crate::autopilot::AutopilotMode;
+use crate::config::{HudRefresh, Layer, Settings};
+use crate::drone::Drone;
+use crate::ghost::Ghost;
+use crate::highscores::HighScoreBoard;
+use crate::stats::RunStats;
+use crate::theme::Theme;
+use crate::upgrades::{self, Effect};
+use crate::trial::TimeTrial;
+
+// Inner size of the bordered playfield, in terminal cells
+pub const FIELD_WIDTH: u16 = 36;
+pub const FIELD_HEIGHT: u16 = 15;
+
+// --- Frame Buffer ---
+// Everything is drawn into a Frame first and written to the terminal in one go
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cell {
+    pub ch: char,
+    // None uses the terminal's default color
+    pub color: Option<Color>,
+}
+
+const BLANK: Cell = Cell { ch: ' ', color: None };
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub width: u16,
+    pub height: u16,
+    // Blank cells between the border and the content; drawing coordinates
+    // are shifted inwards by this much
+    margin: u16,
+    // World position of the frame's top-left cell, when it shows part of a bigger world
+    pub scroll: (u16, u16),
+    cells: Vec<Cell>,
+}
+
+impl Frame {
+    pub fn new(width: u16, height: u16) -> Self {
+        Frame::with_margin(width, height, 0)
+    }
+
+    // A frame fitting `width` x `height` of content inside a blank margin
+    pub fn with_margin(width: u16, height: u16, margin: u16) -> Self {
+        let width = width + 2 * margin;
+        let height = height + 2 * margin;
+        Frame { width, height, margin, scroll: (0, 0), cells: vec![BLANK; width as usize * height as usize] }
+    }
+
+    // Index of the cell drawn at (x, y), if that's inside the frame
+    fn index(&self, x: u16, y: u16) -> Option<usize> {
+        let x = x.checked_sub(self.scroll.0)? + self.margin;
+        let y = y.checked_sub(self.scroll.1)? + self.margin;
+        (x < self.width && y < self.height).then(|| y as usize * self.width as usize + x as usize)
+    }
+
+    // Writes outside the frame are clipped
+    pub fn set_cell(&mut self, x: u16, y: u16, cell: Cell) {
+        if let Some(i) = self.index(x, y) {
+            self.cells[i] = cell;
+        }
+    }
+
+    pub fn cell(&self, x: u16, y: u16) -> Option<Cell> {
+        self.index(x, y).map(|i| self.cells[i])
+    }
+
+    pub fn set(&mut self, x: u16, y: u16, ch: char) {
+        self.set_cell(x, y, Cell { ch, color: None });
+    }
+
+    pub fn put_str(&mut self, x: u16, y: u16, text: &str) {
+        for (i, ch) in text.chars().enumerate() {
+            self.set(x + i as u16, y, ch);
+        }
+    }
+
+    pub fn put_colored(&mut self, x: u16, y: u16, text: &str, color: Color) {
+        self.put_tinted(x, y, text, Some(color));
+    }
+
+    pub fn put_tinted(&mut self, x: u16, y: u16, text: &str, color: Option<Color>) {
+        for (i, ch) in text.chars().enumerate() {
+            self.set_cell(x + i as u16, y, Cell { ch, color });
+        }
+    }
+
+    // A row as terminal text, with color escapes around each run of colored cells
+    pub fn row(&self, y: u16) -> String {
+        let start = y as usize * self.width as usize;
+        let mut line = String::new();
+        let mut run = String::new();
+        let mut run_color = None;
+        for cell in &self.cells[start..start + self.width as usize] {
+            if cell.color != run_color {
+                flush_run(&mut line, &mut run, run_color);
+                run_color = cell.color;
+            }
+            run.push(cell.ch);
+        }
+        flush_run(&mut line, &mut run, run_color);
+        line
+    }
+}
+
+fn flush_run(line: &mut String, run: &mut String, color: Option<Color>) {
+    match color {
+        Some(color) => line.push_str(&run.as_str().with(color).to_string()),
+        None => line.push_str(run),
+    }
+    run.clear();
+}
+
+// --- Colors ---
+pub fn fuel_color(state: FuelState) -> Color {
+    match state {
+        FuelState::Good => Color::Green,
+        FuelState::Low => Color::Yellow,
+        FuelState::Critical => Color::Red,
+    }
+}
+
+// Each resource cycles slowly through its shades, offset per node so the
+// field twinkles instead of pulsing in unison
+const SHIMMER_PERIOD: u32 = 4;
+
+fn resource_shades(kind: Resource) -> &'static [Color] {
+    match kind {
+        Resource::Iron => &[Color::Grey, Color::White],
+        Resource::Crystal => &[Color::Cyan, Color::Blue, Color::Cyan],
+        Resource::Gold => &[Color::Yellow, Color::DarkYellow],
+    }
+}
+
+pub fn shimmer_color(node: &ResourceNode, frame: u32) -> Color {
+    let shades = resource_shades(node.kind);
+    let phase = (node.x as u32).wrapping_mul(7).wrapping_add((node.y as u32).wrapping_mul(13));
+    shades[((frame / SHIMMER_PERIOD).wrapping_add(phase) as usize) % shades.len()]
+}
+
+// The ship is tinted like the fuel bar so urgency reads from the ship itself
+pub fn ship_color(ship: &Ship, fuel_reserve: f32) -> Color {
+    fuel_color(fuel_state(ship.total_fuel(), fuel_reserve))
+}
+
+fn resource_glyph(kind: Resource) -> char {
+    match kind {
+        Resource::Iron => '*',
+        Resource::Crystal => '♦',
+        Resource::Gold => '$',
+    }
+}
+
+// Frames each step of a resource animation is held for
+const ANIMATION_PERIOD: u32 = 3;
+
+pub fn animated_glyph(kind: Resource, frame: u32, animations: &HashMap<Resource, String>) -> char {
+    let sequence: Vec<char> = animations.get(&kind).map(|s| s.chars().collect()).unwrap_or_default();
+    if sequence.is_empty() {
+        return resource_glyph(kind);
+    }
+    sequence[(frame / ANIMATION_PERIOD) as usize % sequence.len()]
+}
+
+// Partly mined rich nodes wear down as they empty; None while untouched
+pub fn depletion_glyph(amount: u32, richness: u32) -> Option<char> {
+    if amount >= richness {
+        None
+    } else if amount * 3 > richness {
+        Some('◈')
+    } else {
+        Some('·')
+    }
+}
+
+// Layers are drawn back to front in the order `Settings::draw_order` gives
+pub fn draw_resources<'a>(
+    frame: &mut Frame,
+    resources: impl IntoIterator<Item = (&'a ResourceNode, bool)>,
+    anim_frame: u32,
+    animations: &HashMap<Resource, String>,
+    scanner: bool,
+) {
+    for (res, fading) in resources {
+        let cell = if fading {
+            Cell { ch: '·', color: Some(Color::DarkGrey) }
+        } else {
+            let ch = depletion_glyph(res.amount, res.richness)
+                .unwrap_or_else(|| animated_glyph(res.kind, anim_frame, animations));
+            // Contamination only gives itself away by the node not shimmering,
+            // unless a scanner marks it outright
+            let color = match res.contaminated {
+                true if scanner => Color::Red,
+                true => shimmer_color(res, 0),
+                false => shimmer_color(res, anim_frame),
+            };
+            Cell { ch, color: Some(color) }
+        };
+        frame.set_cell(res.x, res.y, cell);
+    }
+}
+
+// --- Starfield ---
+// Roughly one cell in STAR_RARITY holds a star
+const STAR_RARITY: u32 = 23;
+const TWINKLE_PERIOD: u32 = 5;
+
+// Deterministic per-cell hash, so stars stay put as the view moves
+fn cell_hash(x: u16, y: u16) -> u32 {
+    let mut h = (x as u32).wrapping_mul(0x9e3779b1) ^ (y as u32).wrapping_mul(0x85ebca77);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2c1b3c6d);
+    h ^ (h >> 12)
+}
+
+pub fn star_at(x: u16, y: u16, anim_frame: u32) -> Option<Cell> {
+    let h = cell_hash(x, y);
+    if !h.is_multiple_of(STAR_RARITY) {
+        return None;
+    }
+    // Each star twinkles on its own phase
+    let bright = (anim_frame / TWINKLE_PERIOD).wrapping_add(h >> 8).is_multiple_of(3);
+    Some(if bright {
+        Cell { ch: '+', color: Some(Color::Grey) }
+    } else {
+        Cell { ch: '.', color: Some(Color::DarkGrey) }
+    })
+}
+
+// Drawn first, so anything else in a cell hides its star
+pub fn draw_starfield(frame: &mut Frame, anim_frame: u32) {
+    let (w, h) = (frame.width - 2 * frame.margin, frame.height - 2 * frame.margin);
+    for sy in 0..h {
+        for sx in 0..w {
+            let (x, y) = (sx + frame.scroll.0, sy + frame.scroll.1);
+            if let Some(star) = star_at(x, y, anim_frame) {
+                frame.set_cell(x, y, star);
+            }
+        }
+    }
+}
+
+// Flashes the edge a boss is about to come in from
+pub fn draw_boss_warning(frame: &mut Frame, edge: Edge, bounds: &Rect, anim_frame: u32) {
+    if !(anim_frame / BLINK_PERIOD).is_multiple_of(2) {
+        return;
+    }
+    let cells: Vec<(u16, u16)> = match edge {
+        Edge::Top => (bounds.x..bounds.x + bounds.w).map(|x| (x, bounds.y)).collect(),
+        Edge::Bottom => (bounds.x..bounds.x + bounds.w).map(|x| (x, bounds.y + bounds.h - 1)).collect(),
+        Edge::Left => (bounds.y..bounds.y + bounds.h).map(|y| (bounds.x, y)).collect(),
+        Edge::Right => (bounds.y..bounds.y + bounds.h).map(|y| (bounds.x + bounds.w - 1, y)).collect(),
+    };
+    for (x, y) in cells {
+        frame.set_cell(x, y, Cell { ch: '!', color: Some(Color::Red) });
+    }
+}
+
+// The next gate stands out; ones already passed are gone
+pub fn draw_gates(frame: &mut Frame, trial: &TimeTrial) {
+    for (i, gate) in trial.gates.iter().enumerate().skip(trial.next) {
+        let cell = if i == trial.next {
+            Cell { ch: '▒', color: Some(Color::Green) }
+        } else {
+            Cell { ch: '░', color: Some(Color::DarkGrey) }
+        };
+        for y in gate.y..gate.y + gate.h {
+            for x in gate.x..gate.x + gate.w {
+                frame.set_cell(x, y, cell);
+            }
+        }
+    }
+}
+
+pub fn draw_station(frame: &mut Frame, (x, y): (u16, u16)) {
+    frame.set_cell(x, y, Cell { ch: '⌂', color: Some(Color::Cyan) });
+}
+
+pub fn draw_walls(frame: &mut Frame, arena: &Arena) {
+    for &(x, y) in arena.walls() {
+        frame.set_cell(x, y, Cell { ch: '█', color: Some(Color::DarkGrey) });
+    }
+}
+
+// Faint and underneath everything else
+pub fn draw_ghost(frame: &mut Frame, ghost: &Ghost, tick: u32, glyph: &str, trail: bool) {
+    if trail {
+        for &(x, y) in ghost.trail(tick) {
+            frame.set_cell(x, y, Cell { ch: '·', color: Some(Color::DarkGrey) });
+        }
+    }
+    if let Some((x, y)) = ghost.position(tick) {
+        frame.put_colored(x, y, glyph, Color::DarkGrey);
+    }
+}
+
+pub fn draw_asteroids(frame: &mut Frame, asteroids: &[Asteroid], theme: &Theme) {
+    for a in asteroids {
+        frame.set_cell(a.x, a.y, Cell { ch: 'O', color: theme.asteroid_color(a) });
+    }
+}
+
+pub fn draw_drone(frame: &mut Frame, drone: &Drone) {
+    frame.set_cell(drone.x, drone.y, Cell { ch: '•', color: Some(Color::Magenta) });
+}
+
+pub fn draw_ship(frame: &mut Frame, ship: &Ship, glyph: &str, fuel_reserve: f32) {
+    frame.put_colored(ship.x, ship.y, glyph, ship_color(ship, fuel_reserve));
+}
+
+fn border_line(left: char, right: char, width: u16) -> String {
+    format!("{}{}{}", left, "═".repeat(width as usize), right)
+}
+
+// Top border with a warning/announcement set into it
+fn banner_line(text: &str, width: u16) -> String {
+    let label = format!(" {} ", text);
+    let rest = (width as usize).saturating_sub(label.chars().count() + 2);
+    format!("╔══{}{}╗", label, "═".repeat(rest))
+}
+
+fn tint(text: String, color: Option<Color>) -> String {
+    let mut line = String::new();
+    let mut run = text;
+    flush_run(&mut line, &mut run, color);
+    line
+}
+
+// Wraps the frame in a border, with an optional banner in the top edge and
+// an optional status line underneath
+pub fn screen_lines(frame: &Frame, banner: Option<&str>, hud: Option<&Frame>, border: Option<Color>) -> Vec<String> {
+    let top = match banner {
+        Some(text) => banner_line(text, frame.width),
+        None => border_line('╔', '╗', frame.width),
+    };
+    let side = tint("║".to_string(), border);
+    let mut lines = vec![tint(top, border)];
+    for y in 0..frame.height {
+        lines.push(format!("{}{}{}", side, frame.row(y), side));
+    }
+    if let Some(hud) = hud {
+        lines.push(tint(border_line('╠', '╣', frame.width), border));
+        for y in 0..hud.height {
+            lines.push(format!("{}{}{}", side, hud.row(y), side));
+        }
+    }
+    lines.push(tint(border_line('╚', '╝', frame.width), border));
+    lines
+}
+
+// --- Terminal Output ---
+// Remembers what is on screen so only changed lines are rewritten each frame
+pub struct Renderer<W: Write> {
+    out: W,
+    // Live stats overlay in the corner of the playfield
+    pub show_stats: bool,
+    pub cargo_readout: CargoReadout,
+    on_screen: Vec<String>,
+    hud_refresh: HudRefresh,
+    last_hud: Option<HudValues>,
+    frames_since_hud: u32,
+    // Presentation clock for cosmetic animation; advances every frame drawn,
+    // unlike the game's tick, which stops while paused
+    anim_frame: u32,
+}
+
+impl<W: Write> Renderer<W> {
+    pub fn new(out: W, hud_refresh: HudRefresh) -> Self {
+        Renderer { out, show_stats: false, cargo_readout: CargoReadout::Count, on_screen: Vec::new(), hud_refresh, last_hud: None, frames_since_hud: 0, anim_frame: 0 }
+    }
+
+    // Raw mode doesn't translate '\n', so every line is positioned explicitly
+    pub fn present(&mut self, lines: &[String]) {
+        for (y, line) in lines.iter().enumerate() {
+            if self.on_screen.get(y) == Some(line) {
+                continue;
+            }
+            queue!(
+                self.out,
+                cursor::MoveTo(0, y as u16),
+                Print(line),
+                terminal::Clear(ClearType::UntilNewLine)
+            ).unwrap();
+        }
+        if lines.len() < self.on_screen.len() {
+            queue!(self.out, cursor::MoveTo(0, lines.len() as u16), terminal::Clear(ClearType::FromCursorDown)).unwrap();
+        }
+        self.out.flush().unwrap();
+        self.on_screen = lines.to_vec();
+    }
+
+    // HUD values shown this frame: the live values when the refresh policy
+    // says so, otherwise whatever was last drawn
+    fn hud_for_frame(&mut self, current: HudValues) -> HudValues {
+        self.frames_since_hud += 1;
+        let due = match (&self.last_hud, self.hud_refresh) {
+            (None, _) => true,
+            (Some(_), HudRefresh::EveryFrame) => true,
+            (Some(_), HudRefresh::EveryNFrames(n)) => self.frames_since_hud >= n,
+        };
+        if due {
+            self.last_hud = Some(current);
+            self.frames_since_hud = 0;
+        }
+        self.last_hud.unwrap_or(current)
+    }
+
+    pub fn game_lines(&mut self, state: &GameState) -> Vec<String> {
+        self.lines_with_banner(state, None)
+    }
+
+    // The game screen with a question in place of the usual banner
+    pub fn prompt_lines(&mut self, state: &GameState, prompt: &str) -> Vec<String> {
+        self.lines_with_banner(state, Some(prompt.to_string()))
+    }
+
+    fn lines_with_banner(&mut self, state: &GameState, prompt: Option<String>) -> Vec<String> {
+        self.anim_frame = self.anim_frame.wrapping_add(1);
+        let hud = self.hud_for_frame(HudValues::new(state, self.cargo_readout));
+        let mut frame = game_frame(state, self.anim_frame);
+        // Overlays are pinned to the screen, not the world
+        frame.scroll = (0, 0);
+        if self.show_stats {
+            draw_stats(&mut frame, state);
+        }
+        if state.bounds.w > state.camera.w || state.bounds.h > state.camera.h {
+            draw_minimap(&mut frame, state);
+        }
+        let banner = prompt.or_else(|| banner_text(state, self.anim_frame));
+        let theme = &state.settings.theme;
+        let hud = state.hud_visible.then(|| hud_frame(&hud, frame.width, theme));
+        screen_lines(&frame, banner.as_deref(), hud.as_ref(), theme.border)
+    }
+}
+
+// --- Game Screen ---
+// Cells of the HUD's fuel gauge; with a reserve tank the last few show the
+// reserve, after a divider
+const FUEL_BAR: u16 = 10;
+const RESERVE_BAR: u16 = 3;
+
+fn bar_blocks(fuel: f32, capacity: f32, width: u16) -> usize {
+    ((fuel / capacity * width as f32).round() as usize).min(width as usize)
+}
+
+// What the HUD's cargo gauge shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CargoReadout {
+    // Units held, e.g. "CARGO 7"
+    Count,
+    // What they'd sell for, e.g. "VALUE 185"
+    Value,
+}
+
+impl CargoReadout {
+    pub fn toggled(self) -> Self {
+        match self {
+            CargoReadout::Count => CargoReadout::Value,
+            CargoReadout::Value => CargoReadout::Count,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            CargoReadout::Count => "CARGO",
+            CargoReadout::Value => "VALUE",
+        }
+    }
+}
+
+// The values the HUD displays, quantized the same way they are drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HudValues {
+    player: ShipHud,
+    ally: Option<ShipHud>,
+    score: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ShipHud {
+    fuel_blocks: usize,
+    // Blocks of the reserve tank's bar, for ships that have one
+    reserve_blocks: Option<usize>,
+    fuel_state: FuelState,
+    cargo_readout: CargoReadout,
+    cargo: u32,
+}
+
+impl ShipHud {
+    fn new(state: &GameState, ship: &Ship, cargo_readout: CargoReadout) -> Self {
+        let cargo = match cargo_readout {
+            CargoReadout::Count => ship.cargo.values().sum(),
+            CargoReadout::Value => state.cargo_value(ship),
+        };
+        let has_reserve = ship.tanks.reserve > 0.0;
+        let main_bar = if has_reserve { FUEL_BAR - RESERVE_BAR - 1 } else { FUEL_BAR };
+        ShipHud {
+            fuel_blocks: bar_blocks(ship.fuel, ship.max_fuel(), main_bar),
+            reserve_blocks: has_reserve.then(|| bar_blocks(ship.reserve_fuel, ship.tanks.reserve, RESERVE_BAR)),
+            fuel_state: fuel_state(ship.total_fuel(), state.settings.fuel_reserve),
+            cargo_readout,
+            cargo,
+        }
+    }
+}
+
+impl HudValues {
+    pub fn new(state: &GameState, cargo_readout: CargoReadout) -> Self {
+        HudValues {
+            player: ShipHud::new(state, &state.ship, cargo_readout),
+            ally: state.ally.as_ref().map(|ally| ShipHud::new(state, ally, cargo_readout)),
+            score: state.score,
+        }
+    }
+}
+
+// `anim_frame` drives purely cosmetic effects, which keep moving while the game is paused
+pub fn game_frame(state: &GameState, anim_frame: u32) -> Frame {
+    let effects = state.settings.effects_enabled();
+    // Without effects everything animated holds its first frame
+    let anim_frame = if effects { anim_frame } else { 0 };
+    let mut frame = Frame::with_margin(FIELD_WIDTH, state.camera.h, state.settings.margin);
+    frame.scroll = (state.camera.x, state.camera.y);
+    if state.settings.starfield && effects {
+        draw_starfield(&mut frame, anim_frame);
+    }
+    draw_walls(&mut frame, &state.arena);
+    draw_station(&mut frame, STATION);
+    if let Some(trial) = &state.trial {
+        draw_gates(&mut frame, trial);
+    }
+    if let Some(warning) = &state.boss_warning {
+        draw_boss_warning(&mut frame, warning.edge, &state.bounds, anim_frame);
+    }
+    // Later layers win where entities overlap
+    for layer in &state.settings.draw_order {
+        draw_layer(&mut frame, *layer, state, anim_frame);
+    }
+    if state.settings.show_hitboxes {
+        draw_hitboxes(&mut frame, state);
+    }
+    frame
+}
+
+fn draw_layer(frame: &mut Frame, layer: Layer, state: &GameState, anim_frame: u32) {
+    let theme = &state.settings.theme;
+    match layer {
+        Layer::Ghost => {
+            if let Some(ghost) = &state.ghost {
+                draw_ghost(frame, ghost, state.tick, &theme.ship_glyph, state.settings.effects_enabled());
+            }
+        }
+        Layer::Resources => {
+            let resources = state.resources.iter().map(|r| (r, state.resource_fading(r)));
+            let scanner = upgrades::has_effect(&state.ship.upgrades, &Effect::Scanner);
+            draw_resources(frame, resources, anim_frame, &state.settings.resource_animations, scanner);
+        }
+        Layer::Asteroids => draw_asteroids(frame, &state.asteroids, theme),
+        Layer::Drone => {
+            if let Some(drone) = &state.drone {
+                draw_drone(frame, drone);
+            }
+        }
+        Layer::Ships => {
+            if let Some(ally) = &state.ally {
+                draw_ship(frame, ally, &theme.ally_glyph, state.settings.fuel_reserve);
+            }
+            draw_ship(frame, &state.ship, &theme.ship_glyph, state.settings.fuel_reserve);
+        }
+    }
+}
+
+// Debug overlay on top of everything: hit cells in red, graze rings shaded
+fn draw_hitboxes(frame: &mut Frame, state: &GameState) {
+    let mut mark = |rect: Rect, hit: bool| {
+        for y in rect.y..rect.y + rect.h {
+            for x in rect.x..rect.x + rect.w {
+                let Some(cell) = frame.cell(x, y) else { continue };
+                if hit {
+                    frame.set_cell(x, y, Cell { ch: cell.ch, color: Some(Color::Red) });
+                } else if cell == BLANK {
+                    frame.set_cell(x, y, Cell { ch: '░', color: Some(Color::DarkRed) });
+                }
+            }
+        }
+    };
+    for a in &state.asteroids {
+        let (core, graze) = asteroid_zones(a, state.settings.graze_radius);
+        if state.settings.graze_radius > 0 {
+            mark(graze, false);
+        }
+        mark(core, true);
+    }
+    for ship in std::iter::once(&state.ship).chain(state.ally.as_ref()) {
+        mark(ship_hitbox(ship), true);
+    }
+}
+
+// Frames a blinking banner spends on, then off
+const BLINK_PERIOD: u32 = 4;
+
+// Warnings set into the top border, most urgent first
+fn banner_text(state: &GameState, anim_frame: u32) -> Option<String> {
+    let mut warnings = Vec::new();
+    if let Some(warning) = hardcore_warning(&state.settings) {
+        warnings.push(warning);
+    }
+    if state.paused {
+        warnings.push(if (anim_frame / BLINK_PERIOD).is_multiple_of(2) { "PAUSED" } else { "      " }.to_string());
+    }
+    if let Some(warning) = &state.boss_warning {
+        let secs = warning.spawns_at.saturating_sub(state.elapsed).as_secs_f32().ceil();
+        warnings.push(format!("BOSS FROM {} IN {:.0}s", warning.edge.name(), secs));
+    }
+    if let Some(trial) = &state.trial {
+        warnings.push(format!("GATE {}/{} {:.1}s", (trial.next + 1).min(trial.gates.len()), trial.gates.len(), state.elapsed.as_secs_f32()));
+    }
+    if state.resource_rain() {
+        warnings.push("*** RESOURCE RAIN ***".to_string());
+    }
+    if state.ship.hull < MAX_HULL {
+        warnings.push(format!("HULL {:.0}%", state.ship.hull / MAX_HULL * 100.0));
+    }
+    if state.field_is_dense() {
+        warnings.push("DENSE FIELD!".to_string());
+    }
+    if let Some(mode) = state.autopilot_status() {
+        warnings.push(format!("AUTOPILOT: {}", if mode == AutopilotMode::Flee { "FLEE" } else { "SEEK" }));
+    }
+    if state.docked() {
+        warnings.push("DOCKED: C=FUEL".to_string());
+    }
+    if let Some((done, total)) = state.mining_progress() {
+        warnings.push(format!("MINING {}/{}", done, total));
+    }
+    (!warnings.is_empty()).then(|| warnings.join(" | "))
+}
+
+pub fn stats_lines(stats: &RunStats, elapsed: Duration) -> [String; 3] {
+    [
+        format!("MOVES/MIN {:.1}", stats.moves_per_minute(elapsed)),
+        format!("MINES/MIN {:.1}", stats.mines_per_minute(elapsed)),
+        format!("DISTANCE  {}", stats.distance),
+    ]
+}
+
+// Drawn over the top-right corner of the playfield
+fn draw_stats(frame: &mut Frame, state: &GameState) {
+    for (y, line) in stats_lines(&state.stats, state.elapsed).iter().enumerate() {
+        let x = FIELD_WIDTH.saturating_sub(line.chars().count() as u16 + 1);
+        frame.put_colored(x, y as u16, line, Color::Cyan);
+    }
+}
+
+// --- Minimap ---
+// Size in cells of the overview of a world bigger than the screen
+const MINIMAP_WIDTH: u16 = 10;
+const MINIMAP_HEIGHT: u16 = 4;
+
+// The minimap dot for a resource node, colored by its worth
+pub fn minimap_dot(node: &ResourceNode, value: u32, theme: &Theme) -> Cell {
+    let rich = theme.minimap_richness && node.richness > 1;
+    Cell { ch: if rich { '●' } else { '•' }, color: Some(theme.minimap_color(value)) }
+}
+
+// Drawn over the bottom-left corner of the playfield; where nodes share a
+// cell the most valuable shows, and the ship shows over everything
+fn draw_minimap(frame: &mut Frame, state: &GameState) {
+    let b = state.bounds;
+    // Drawing coordinates already sit inside the margin, so measure from the content's bottom
+    let top = frame.height.saturating_sub(2 * frame.margin + MINIMAP_HEIGHT);
+    let to_map = |x: u16, y: u16| {
+        let mx = ((x - b.x) as u32 * MINIMAP_WIDTH as u32 / b.w as u32) as u16;
+        let my = ((y - b.y) as u32 * MINIMAP_HEIGHT as u32 / b.h as u32) as u16;
+        (mx, top + my)
+    };
+    for y in top..top + MINIMAP_HEIGHT {
+        frame.put_colored(0, y, &"·".repeat(MINIMAP_WIDTH as usize), Color::DarkGrey);
+    }
+    let mut shown: HashMap<(u16, u16), u32> = HashMap::new();
+    for node in &state.resources {
+        let value = state.resource_value(node.kind);
+        let cell = to_map(node.x, node.y);
+        if shown.get(&cell).is_none_or(|&best| value > best) {
+            shown.insert(cell, value);
+            frame.set_cell(cell.0, cell.1, minimap_dot(node, value, &state.settings.theme));
+        }
+    }
+    let (x, y) = to_map(state.ship.x, state.ship.y);
+    frame.set_cell(x, y, Cell { ch: '@', color: Some(Color::White) });
+}
+
+// Text past the right edge is clipped by the frame. In two-player mode the
+// ally gets a second row.
+fn hud_frame(hud: &HudValues, width: u16, theme: &Theme) -> Frame {
+    let mut frame = Frame::new(width, if hud.ally.is_some() { 2 } else { 1 });
+    draw_gauge(&mut frame, 0, "FUEL", &hud.player, theme);
+    draw_readout(&mut frame, 26, 0, "SCORE", hud.score, theme);
+    if let Some(ally) = &hud.ally {
+        draw_gauge(&mut frame, 1, "P2", ally, theme);
+    }
+    frame
+}
+
+fn draw_gauge(frame: &mut Frame, y: u16, label: &str, ship: &ShipHud, theme: &Theme) {
+    frame.put_tinted(1, y, label, theme.hud_label);
+    let color = fuel_color(ship.fuel_state);
+    match ship.reserve_blocks {
+        Some(reserve) => {
+            frame.put_colored(6, y, &bar(ship.fuel_blocks, FUEL_BAR - RESERVE_BAR - 1), color);
+            frame.put_tinted(6 + FUEL_BAR - RESERVE_BAR - 1, y, "|", theme.hud_label);
+            frame.put_colored(6 + FUEL_BAR - RESERVE_BAR, y, &bar(reserve, RESERVE_BAR), Color::DarkCyan);
+        }
+        None => frame.put_colored(6, y, &bar(ship.fuel_blocks, FUEL_BAR), color),
+    }
+    draw_readout(frame, 17, y, ship.cargo_readout.label(), ship.cargo, theme);
+}
+
+fn bar(filled: usize, width: u16) -> String {
+    format!("{}{}", "█".repeat(filled), "░".repeat(width as usize - filled))
+}
+
+// "LABEL value", each part in its theme color
+fn draw_readout(frame: &mut Frame, x: u16, y: u16, label: &str, value: impl std::fmt::Display, theme: &Theme) {
+    frame.put_tinted(x, y, label, theme.hud_label);
+    frame.put_tinted(x + label.chars().count() as u16 + 1, y, &value.to_string(), theme.hud_value);
+}
+
+// --- Welcome Screen ---
+const TITLE_TEXT: [&str; 7] = [
+    "RUSTY SPACE MINER",
+    "",
+    "Use WASD to move, SPACE to mine",
+    "Avoid asteroids!",
+    "Press Q to quit",
+    "",
+    "Press any key to start...",
+];
+
+// Title text is drawn over the drifting field on a cleared backdrop
+pub fn welcome_lines(asteroids: &[Asteroid], settings: &Settings) -> Vec<String> {
+    let mut frame = Frame::new(FIELD_WIDTH, FIELD_HEIGHT);
+    draw_asteroids(&mut frame, asteroids, &settings.theme);
+
+    let box_width = TITLE_TEXT.iter().map(|t| t.chars().count()).max().unwrap_or(0) as u16 + 2;
+    let left = (FIELD_WIDTH - box_width) / 2;
+    let top = (FIELD_HEIGHT - TITLE_TEXT.len() as u16) / 2;
+    for (i, text) in TITLE_TEXT.iter().enumerate() {
+        let y = top + i as u16;
+        let x = (FIELD_WIDTH - text.chars().count() as u16) / 2;
+        frame.put_str(left, y, &" ".repeat(box_width as usize));
+        frame.put_str(x, y, text);
+    }
+    screen_lines(&frame, hardcore_warning(settings).as_deref(), None, settings.theme.border)
+}
+
+// The high-score table, shown between demo runs in the attract loop
+pub fn high_score_lines(board: &HighScoreBoard, settings: &Settings) -> Vec<String> {
+    let mut frame = Frame::new(FIELD_WIDTH, FIELD_HEIGHT);
+    let mut rows = board.lines();
+    if rows.is_empty() {
+        rows.push("No scores yet".to_string());
+    }
+    let width = rows.iter().map(|r| r.chars().count()).max().unwrap_or(0) as u16;
+    let left = FIELD_WIDTH.saturating_sub(width) / 2;
+    let top = FIELD_HEIGHT.saturating_sub(rows.len() as u16) / 2;
+    for (i, row) in rows.iter().enumerate() {
+        frame.put_str(left, top + i as u16, row);
+    }
+    screen_lines(&frame, Some("HIGH SCORES"), None, settings.theme.border)
+}
+
+fn hardcore_warning(settings: &Settings) -> Option<String> {
+    settings.hardcore.then(|| format!("HARDCORE: DEATH DELETES SLOT {}", settings.save_slot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arena::Arena;
+
+    fn game(settings: Settings) -> GameState {
+        GameState::new(Settings { skip_intro: true, seed: Some(7), ..settings }, Arena::default())
+    }
+
+    #[test]
+    fn rich_node_wears_down_as_it_empties() {
+        let node = ResourceNode::new(2, 1, Resource::Crystal, Duration::ZERO).rich(3);
+        let mut frame = Frame::new(4, 3);
+        draw_resources(&mut frame, [(&node, false)], 0, &HashMap::new(), false);
+        assert_eq!(frame.cell(2, 1).unwrap().ch, '♦');
+
+        let two_thirds = ResourceNode { amount: 2, ..node.clone() };
+        draw_resources(&mut frame, [(&two_thirds, false)], 0, &HashMap::new(), false);
+        assert_eq!(frame.cell(2, 1).unwrap().ch, '◈');
+
+        let one_third = ResourceNode { amount: 1, ..node };
+        draw_resources(&mut frame, [(&one_third, false)], 0, &HashMap::new(), false);
+        assert_eq!(frame.cell(2, 1).unwrap().ch, '·');
+    }
+
+    #[test]
+    fn hitbox_overlay_marks_the_whole_ship() {
+        let mut state = game(Settings { show_hitboxes: true, ..Settings::default() });
+        state.asteroids.clear();
+        state.resources.clear();
+        let frame = game_frame(&state, 0);
+        let mut red = Vec::new();
+        for y in 0..state.camera.h {
+            for x in 0..FIELD_WIDTH {
+                if frame.cell(x, y).unwrap().color == Some(Color::Red) {
+                    red.push((x, y));
+                }
+            }
+        }
+        let ship = &state.ship;
+        let expected: Vec<_> = (ship.x..ship.x + crate::SHIP_WIDTH).map(|x| (x, ship.y)).collect();
+        assert_eq!(red, expected);
+    }
+
+    #[test]
+    fn rich_node_gets_a_bigger_minimap_dot() {
+        let theme = Theme::default();
+        let plain = ResourceNode::new(0, 0, Resource::Gold, Duration::ZERO);
+        let rich = plain.clone().rich(3);
+        assert_ne!(minimap_dot(&rich, 50, &theme).ch, minimap_dot(&plain, 50, &theme).ch);
+        let flat = Theme { minimap_richness: false, ..Theme::default() };
+        assert_eq!(minimap_dot(&rich, 50, &flat), minimap_dot(&plain, 50, &flat));
+    }
+
+    #[test]
+    fn valuable_node_gets_a_different_minimap_color() {
+        let theme = Theme::default();
+        let node = ResourceNode::new(0, 0, Resource::Iron, Duration::ZERO);
+        assert_ne!(minimap_dot(&node, 5, &theme).color, minimap_dot(&node, 60, &theme).color);
+    }
+
+    #[test]
+    fn minimap_stays_inside_the_margin() {
+        let mut state = game(Settings { margin: 2, ..Settings::default() });
+        state.bounds.w *= 2;
+        state.bounds.h *= 2;
+        let mut frame = Frame::with_margin(FIELD_WIDTH, state.camera.h, 2);
+        draw_minimap(&mut frame, &state);
+        let bottom = state.camera.h - 1;
+        assert_ne!(frame.cell(0, bottom), Some(BLANK));
+        assert_eq!(frame.cell(0, bottom - MINIMAP_HEIGHT), Some(BLANK));
+    }
+
+    #[test]
+    fn welcome_screen_shows_the_preview() {
+        let settings = Settings::default();
+        let asteroids: Vec<Asteroid> = (0..FIELD_WIDTH).map(|x| Asteroid { x, y: 0, vx: -1, vy: 0 }).collect();
+        let with = welcome_lines(&asteroids, &settings);
+        let without = welcome_lines(&[], &settings);
+        assert_ne!(with, without);
+        // Only the asteroids' row differs
+        let changed = with.iter().zip(&without).filter(|(a, b)| a != b).count();
+        assert_eq!(changed, 1);
+    }
+
+    fn hud_with_score(score: u32) -> HudValues {
+        let mut state = game(Settings::default());
+        state.score = score;
+        HudValues::new(&state, CargoReadout::Count)
+    }
+
+    #[test]
+    fn unchanged_lines_are_not_rewritten() {
+        let mut renderer = Renderer::new(Vec::new(), HudRefresh::EveryFrame);
+        let lines = vec!["one".to_string(), "two".to_string()];
+        renderer.present(&lines);
+        let written = renderer.out.len();
+        renderer.present(&lines);
+        assert_eq!(renderer.out.len(), written);
+        renderer.present(&["one".to_string(), "three".to_string()]);
+        let update = String::from_utf8_lossy(&renderer.out[written..]).to_string();
+        assert!(update.contains("three") && !update.contains("one"));
+    }
+
+    #[test]
+    fn throttled_hud_holds_its_values_between_refreshes() {
+        let mut renderer = Renderer::new(Vec::new(), HudRefresh::EveryNFrames(3));
+        assert_eq!(renderer.hud_for_frame(hud_with_score(0)).score, 0);
+        assert_eq!(renderer.hud_for_frame(hud_with_score(10)).score, 0);
+        assert_eq!(renderer.hud_for_frame(hud_with_score(20)).score, 0);
+        assert_eq!(renderer.hud_for_frame(hud_with_score(30)).score, 30);
+    }
+
+    #[test]
+    fn an_unchanged_hud_is_not_rewritten() {
+        let mut state = game(Settings::default());
+        state.asteroids.clear();
+        let mut renderer = Renderer::new(Vec::new(), HudRefresh::EveryFrame);
+        let lines = renderer.game_lines(&state);
+        renderer.present(&lines);
+        let written = renderer.out.len();
+        // The ship moves, but nothing the HUD shows changes
+        state.ship.x += 1;
+        let lines = renderer.game_lines(&state);
+        renderer.present(&lines);
+        let update = String::from_utf8_lossy(&renderer.out[written..]).to_string();
+        assert!(!update.is_empty());
+        assert!(!update.contains("FUEL") && !update.contains("SCORE"));
+    }
+
+    #[test]
+    fn dense_field_shows_a_warning() {
+        let mut state = game(Settings { dense_field_threshold: 0.0, ..Settings::default() });
+        assert!(banner_text(&state, 0).unwrap().contains("DENSE FIELD!"));
+        state.settings.dense_field_threshold = 1.0;
+        assert!(banner_text(&state, 0).is_none_or(|b| !b.contains("DENSE")));
+    }
+
+    #[test]
+    fn ship_wears_the_fuel_gauge_color() {
+        let mut state = game(Settings::default());
+        let reserve = state.settings.fuel_reserve;
+        for (fuel, color) in [(100.0, Color::Green), (40.0, Color::Yellow), (reserve, Color::Red)] {
+            state.ship.fuel = fuel;
+            let hud = HudValues::new(&state, CargoReadout::Count);
+            assert_eq!(ship_color(&state.ship, reserve), color);
+            assert_eq!(fuel_color(hud.player.fuel_state), color);
+        }
+    }
+
+    #[test]
+    fn margin_pads_the_frame_on_every_side() {
+        let mut frame = Frame::with_margin(4, 3, 2);
+        assert_eq!((frame.width, frame.height), (8, 7));
+        frame.set(0, 0, 'x');
+        assert_eq!(frame.cells[2 * 8 + 2].ch, 'x');
+        frame.set(20, 0, 'z');
+        assert!(!frame.cells.iter().any(|c| c.ch == 'z'));
+    }
+
+    #[test]
+    fn margin_grows_the_game_screen() {
+        let plain = game(Settings::default());
+        let padded = game(Settings { margin: 2, ..Settings::default() });
+        let mut renderer = Renderer::new(Vec::new(), HudRefresh::EveryFrame);
+        let (plain, padded) = (renderer.game_lines(&plain), renderer.game_lines(&padded));
+        assert_eq!(padded.len(), plain.len() + 4);
+    }
+
+    #[test]
+    fn stats_overlay_only_shows_when_toggled() {
+        let state = game(Settings::default());
+        let mut renderer = Renderer::new(Vec::new(), HudRefresh::EveryFrame);
+        assert!(!renderer.game_lines(&state).concat().contains("MOVES/MIN"));
+        renderer.show_stats = true;
+        assert!(renderer.game_lines(&state).concat().contains("MOVES/MIN"));
+    }
+
+    #[test]
+    fn shimmer_is_deterministic_and_stays_in_the_resources_shades() {
+        let node = ResourceNode::new(3, 4, Resource::Gold, Duration::ZERO);
+        for frame in 0..20 {
+            assert_eq!(shimmer_color(&node, frame), shimmer_color(&node, frame));
+            assert!(resource_shades(Resource::Gold).contains(&shimmer_color(&node, frame)));
+        }
+        // Holds each shade for a few frames before moving on
+        assert_eq!(shimmer_color(&node, 0), shimmer_color(&node, SHIMMER_PERIOD - 1));
+        assert_ne!(shimmer_color(&node, 0), shimmer_color(&node, SHIMMER_PERIOD));
+    }
+
+    #[test]
+    fn neighbouring_nodes_shimmer_out_of_step() {
+        let a = ResourceNode::new(3, 4, Resource::Iron, Duration::ZERO);
+        let b = ResourceNode::new(4, 4, Resource::Iron, Duration::ZERO);
+        assert_ne!(shimmer_color(&a, 0), shimmer_color(&b, 0));
+    }
+
+    #[test]
+    fn animation_steps_through_the_configured_frames() {
+        let animations = HashMap::from([(Resource::Gold, "$S".to_string())]);
+        assert_eq!(animated_glyph(Resource::Gold, 0, &animations), '$');
+        assert_eq!(animated_glyph(Resource::Gold, ANIMATION_PERIOD - 1, &animations), '$');
+        assert_eq!(animated_glyph(Resource::Gold, ANIMATION_PERIOD, &animations), 'S');
+        assert_eq!(animated_glyph(Resource::Gold, 2 * ANIMATION_PERIOD, &animations), '$');
+    }
+
+    #[test]
+    fn unanimated_resource_keeps_its_glyph() {
+        let animations = HashMap::from([(Resource::Gold, String::new())]);
+        assert_eq!(animated_glyph(Resource::Gold, 5, &animations), '$');
+        assert_eq!(animated_glyph(Resource::Iron, 5, &animations), '*');
+    }
+
+    #[test]
+    fn perf_mode_freezes_animation() {
+        let mut state = game(Settings { perf: true, ..Settings::default() });
+        state.resources = vec![ResourceNode::new(2, 2, Resource::Crystal, Duration::ZERO)];
+        let first = game_frame(&state, 0);
+        assert_eq!(game_frame(&state, ANIMATION_PERIOD).cell(2, 2), first.cell(2, 2));
+    }
+
+    #[test]
+    fn animation_clock_runs_while_paused() {
+        let state = GameState { paused: true, ..game(Settings::default()) };
+        let mut renderer = Renderer::new(Vec::new(), HudRefresh::EveryFrame);
+        renderer.game_lines(&state);
+        renderer.game_lines(&state);
+        assert_eq!(renderer.anim_frame, 2);
+    }
+
+    #[test]
+    fn draw_order_decides_what_shows_where_entities_overlap() {
+        let shown = |draw_order: Vec<Layer>| {
+            let mut state = game(Settings { draw_order, ..Settings::default() });
+            state.asteroids = vec![Asteroid { x: 4, y: 4, vx: 0, vy: 0 }];
+            state.resources = vec![ResourceNode::new(4, 4, Resource::Iron, Duration::ZERO)];
+            game_frame(&state, 0).cell(4, 4).unwrap().ch
+        };
+        assert_eq!(shown(vec![Layer::Resources, Layer::Asteroids]), 'O');
+        assert_eq!(shown(vec![Layer::Asteroids, Layer::Resources]), '*');
+        // A layer left out isn't drawn at all
+        assert_eq!(shown(vec![Layer::Ships]), ' ');
+    }
+
+    // Cells of the field that hold a star
+    fn star_cells() -> Vec<(u16, u16)> {
+        (0..FIELD_HEIGHT).flat_map(|y| (0..FIELD_WIDTH).map(move |x| (x, y)))
+            .filter(|&(x, y)| star_at(x, y, 0).is_some())
+            .collect()
+    }
+
+    #[test]
+    fn stars_stay_put_from_frame_to_frame() {
+        let stars = star_cells();
+        assert!(!stars.is_empty());
+        for &(x, y) in &stars {
+            assert_eq!(star_at(x, y, 0), star_at(x, y, 0));
+            // Twinkling changes a star's look, never where it is
+            assert!(star_at(x, y, 1000).is_some());
+        }
+    }
+
+    #[test]
+    fn entities_draw_over_stars() {
+        let (x, y) = star_cells()[0];
+        let mut state = game(Settings { starfield: true, ..Settings::default() });
+        state.resources.clear();
+        state.asteroids = vec![Asteroid { x, y, vx: 0, vy: 0 }];
+        assert_eq!(game_frame(&state, 0).cell(x, y).unwrap().ch, 'O');
+        state.asteroids.clear();
+        assert_eq!(game_frame(&state, 0).cell(x, y), star_at(x, y, 0));
+    }
+
+    #[test]
+    fn border_and_hud_wear_the_theme_colors() {
+        let mut settings = Settings::default();
+        settings.theme.border = Some(Color::Magenta);
+        settings.theme.hud_label = Some(Color::Cyan);
+        settings.theme.hud_value = Some(Color::DarkYellow);
+        let state = game(settings);
+        let hud = hud_frame(&HudValues::new(&state, CargoReadout::Count), FIELD_WIDTH, &state.settings.theme);
+        assert_eq!(hud.cell(1, 0), Some(Cell { ch: 'F', color: Some(Color::Cyan) }));
+        // "SCORE 0" starts at column 26
+        assert_eq!(hud.cell(32, 0), Some(Cell { ch: '0', color: Some(Color::DarkYellow) }));
+
+        let lines = screen_lines(&Frame::new(4, 1), None, None, Some(Color::Magenta));
+        assert_eq!(lines[0], "╔════╗".with(Color::Magenta).to_string());
+        assert!(lines[1].starts_with(&"║".with(Color::Magenta).to_string()));
+        let plain = screen_lines(&Frame::new(4, 1), None, None, None);
+        assert_eq!(plain[0], "╔════╗");
+    }
+
+    #[test]
+    fn cargo_readout_switches_between_count_and_value() {
+        let mut state = game(Settings::default());
+        state.ship.cargo.insert(Resource::Iron, 2);
+        state.ship.cargo.insert(Resource::Gold, 1);
+        let hud_text = |readout| {
+            let hud = hud_frame(&HudValues::new(&state, readout), FIELD_WIDTH, &state.settings.theme);
+            (0..FIELD_WIDTH).map(|x| hud.cell(x, 0).unwrap().ch).collect::<String>()
+        };
+        let readout = CargoReadout::Count;
+        assert!(hud_text(readout).contains("CARGO 3"));
+        let value = state.cargo_value(&state.ship);
+        assert!(value > 3);
+        assert!(hud_text(readout.toggled()).contains(&format!("VALUE {}", value)));
+        assert_eq!(readout.toggled().toggled(), readout);
+    }
+
+    #[test]
+    fn scanner_flags_contaminated_nodes_in_red() {
+        let tainted = ResourceNode { contaminated: true, ..ResourceNode::new(2, 1, Resource::Iron, Duration::ZERO) };
+        let clean = ResourceNode::new(4, 1, Resource::Iron, Duration::ZERO);
+        let color_at = |scanner, x| {
+            let mut frame = Frame::new(8, 3);
+            draw_resources(&mut frame, [(&tainted, false), (&clean, false)], 0, &HashMap::new(), scanner);
+            frame.cell(x, 1).unwrap().color
+        };
+        assert_eq!(color_at(true, 2), Some(Color::Red));
+        assert_ne!(color_at(false, 2), Some(Color::Red));
+        assert_ne!(color_at(true, 4), Some(Color::Red));
+    }
+}
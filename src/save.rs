@@ -0,0 +1,86 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use crate::{ActiveEvent, Asteroid, BossWarning, ResourceNode, Ship};
+use crate::stats::RunStats;
+use crate::storage;
+
+// --- Save Slots ---
+// Each slot keeps a career: credits banked across every run played on it
+pub fn slot_path(slot: u8) -> PathBuf {
+    PathBuf::from(format!("save{}.json", slot))
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SaveSlot {
+    pub credits: u64,
+    pub runs: u32,
+    // A run left mid-way with "save and quit", picked up next time the slot
+    // is played
+    pub run: Option<SavedRun>,
+}
+
+// Enough of a run to carry on from where it was left
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedRun {
+    pub seed: u64,
+    pub score: u32,
+    pub credits: u32,
+    pub tick: u32,
+    pub elapsed: Duration,
+    pub ship: Ship,
+    pub asteroids: Vec<Asteroid>,
+    pub resources: Vec<ResourceNode>,
+    // Progress through the run's events, so none of them fire twice
+    #[serde(default)]
+    pub milestones_reached: usize,
+    #[serde(default)]
+    pub difficulty_bumps: u32,
+    #[serde(default)]
+    pub active_event: Option<ActiveEvent>,
+    #[serde(default)]
+    pub boss_warning: Option<BossWarning>,
+    #[serde(default)]
+    pub stats: RunStats,
+    #[serde(default)]
+    pub combo: u32,
+    #[serde(default)]
+    pub last_mine_at: Option<Duration>,
+    // Ship position at each tick so far, which the ghost is recorded from
+    #[serde(default)]
+    pub path: Vec<(u16, u16)>,
+    // Encoded inputs so far, which go in the run's high-score entry
+    #[serde(default)]
+    pub input_log: String,
+}
+
+impl SaveSlot {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        storage::load_or_default(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        storage::save(self, path)
+    }
+
+    // Removes the slot's saved run, if any, so it's only resumed once
+    pub fn take_run(path: &Path) -> Result<Option<SavedRun>, String> {
+        let mut slot = SaveSlot::load(path)?;
+        let run = slot.run.take();
+        if run.is_some() {
+            slot.save(path)?;
+        }
+        Ok(run)
+    }
+
+    // Deleting a slot that was never saved is fine
+    pub fn delete(path: &Path) -> Result<(), String> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("could not delete {}: {}", path.display(), e))
+            }
+            _ => Ok(()),
+        }
+    }
+}
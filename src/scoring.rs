@@ -0,0 +1,58 @@
+use serde::{Serialize, Deserialize};
+use crate::Resource;
+
+// --- Scoring ---
+// Points for every mined node, before any multipliers
+const BASE_POINTS: u32 = 10;
+// Each combo step adds a quarter of the base, up to double points
+const COMBO_STEP_PERCENT: u32 = 25;
+const MAX_COMBO: u32 = 4;
+// Gold pays this many times over during a gold rush
+const GOLD_RUSH_MULTIPLIER: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScoringMode {
+    Standard,
+    // Gold is worth far more than anything else
+    GoldRush,
+}
+
+// `combo` counts the mines chained right before this one; `percent` comes from
+// the difficulty curve
+pub fn score_for_mine(resource: Resource, combo: u32, percent: u32, mode: ScoringMode) -> u32 {
+    let mut points = BASE_POINTS * (100 + COMBO_STEP_PERCENT * combo.min(MAX_COMBO)) / 100;
+    if mode == ScoringMode::GoldRush && resource == Resource::Gold {
+        points *= GOLD_RUSH_MULTIPLIER;
+    }
+    points * percent / 100
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_mine_scores_the_base_points() {
+        assert_eq!(score_for_mine(Resource::Iron, 0, 100, ScoringMode::Standard), BASE_POINTS);
+        assert_eq!(score_for_mine(Resource::Gold, 0, 100, ScoringMode::Standard), BASE_POINTS);
+    }
+
+    #[test]
+    fn combo_builds_up_to_double_points() {
+        assert_eq!(score_for_mine(Resource::Iron, 1, 100, ScoringMode::Standard), 12);
+        assert_eq!(score_for_mine(Resource::Iron, 2, 100, ScoringMode::Standard), 15);
+        assert_eq!(score_for_mine(Resource::Iron, MAX_COMBO, 100, ScoringMode::Standard), 2 * BASE_POINTS);
+        assert_eq!(score_for_mine(Resource::Iron, 10, 100, ScoringMode::Standard), 2 * BASE_POINTS);
+    }
+
+    #[test]
+    fn multipliers_stack() {
+        // Hard difficulty pays half as much again
+        assert_eq!(score_for_mine(Resource::Iron, 0, 150, ScoringMode::Standard), 15);
+        // A gold rush only boosts Gold
+        assert_eq!(score_for_mine(Resource::Crystal, 0, 100, ScoringMode::GoldRush), BASE_POINTS);
+        assert_eq!(score_for_mine(Resource::Gold, 0, 100, ScoringMode::GoldRush), 30);
+        // Full combo, gold rush and hard difficulty together
+        assert_eq!(score_for_mine(Resource::Gold, MAX_COMBO, 150, ScoringMode::GoldRush), 90);
+    }
+}
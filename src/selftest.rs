@@ -0,0 +1,108 @@
+use std::time::Duration;
+use crate::arena::Arena;
+use crate::config::Settings;
+use crate::{mine_duration, Asteroid, DeathCause, GameMode, GameState, InputEvent, Resource, ResourceNode, SHIP_CLEARANCE};
+use crate::upgrades::find_upgrade;
+
+// --- Self-test ---
+// Deterministic scenarios run against the real systems without a terminal,
+// for a quick smoke test: `rusty-space-miner --selftest`
+
+const TICK: Duration = Duration::from_millis(100);
+
+// A fixed-seed game on default settings, ignoring settings.json
+fn fresh_game() -> GameState {
+    let settings = Settings { skip_intro: true, seed: Some(1), ..Settings::default() };
+    GameState::new(settings, Arena::default())
+}
+
+fn check(ok: bool, failure: &str) -> Result<(), String> {
+    if ok { Ok(()) } else { Err(failure.to_string()) }
+}
+
+fn spawn() -> Result<(), String> {
+    let state = fresh_game();
+    let expected = state.difficulty().starting_asteroids as usize;
+    check(state.asteroids.len() == expected, "wrong number of starting asteroids")?;
+    let crowded = state.asteroids.iter()
+        .any(|a| a.x.abs_diff(state.ship.x) <= SHIP_CLEARANCE && a.y.abs_diff(state.ship.y) <= SHIP_CLEARANCE);
+    check(!crowded, "asteroid spawned next to the ship")
+}
+
+fn mine() -> Result<(), String> {
+    let mut state = fresh_game();
+    let (x, y) = (state.ship.x, state.ship.y);
+    state.resources = vec![ResourceNode::new(x, y, Resource::Iron, Duration::ZERO)];
+    state.step(&InputEvent::Mine, TICK);
+    check(state.ship.cargo[&Resource::Iron] == 1, "iron not added to cargo")?;
+    check(state.resources.is_empty(), "mined node left on the field")?;
+    check(state.score > 0, "mining didn't score")
+}
+
+fn collide() -> Result<(), String> {
+    let mut state = fresh_game();
+    let (x, y) = (state.ship.x + 1, state.ship.y);
+    state.asteroids = vec![Asteroid { x, y, vx: 0, vy: 0 }];
+    state.step(&InputEvent::Right, TICK);
+    check(state.mode == GameMode::GameOver, "flying into an asteroid didn't end the game")?;
+    check(state.death_cause == Some(DeathCause::Asteroid), "wrong cause of death")
+}
+
+fn refuel() -> Result<(), String> {
+    let mut state = fresh_game();
+    state.ship.fuel = 50.0;
+    let (x, y) = (state.ship.x, state.ship.y);
+    state.resources = vec![ResourceNode::new(x, y, Resource::Crystal, Duration::ZERO)];
+    for _ in 0..Resource::Crystal.hardness() {
+        state.step(&InputEvent::Mine, TICK);
+    }
+    check(state.ship.cargo[&Resource::Crystal] == 1, "crystal not mined")?;
+    check(state.ship.fuel > 50.0, "mining crystal didn't refuel")
+}
+
+fn laser() -> Result<(), String> {
+    let plain = mine_duration(Resource::Gold, &[]);
+    let laser = find_upgrade("Laser", &[]).ok_or("no built-in laser")?;
+    let lasered = mine_duration(Resource::Gold, &[laser]);
+    check(lasered < plain, "laser didn't speed up mining")?;
+    check(lasered == plain.div_ceil(2), "laser should halve mining time")
+}
+
+type Scenario = fn() -> Result<(), String>;
+
+const SCENARIOS: [(&str, Scenario); 5] = [
+    ("spawn", spawn),
+    ("mine", mine),
+    ("collide", collide),
+    ("refuel", refuel),
+    ("laser", laser),
+];
+
+// Prints a line per scenario; true if they all passed
+pub fn run() -> bool {
+    let mut passed = true;
+    for (name, scenario) in SCENARIOS {
+        match scenario() {
+            Ok(()) => println!("ok      {}", name),
+            Err(e) => {
+                println!("FAILED  {}: {}", name, e);
+                passed = false;
+            }
+        }
+    }
+    passed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_scenario_passes() {
+        for (name, scenario) in SCENARIOS {
+            if let Err(e) = scenario() {
+                panic!("{}: {}", name, e);
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use std::io::{stdout, Write};
+use std::time::Duration;
+
+// --- Sound ---
+// Audible cues via the terminal bell. Bells sent back to back merge into one
+// sound on most terminals, so repeated beeps are spaced out.
+const BEEP_GAP: Duration = Duration::from_millis(120);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cue {
+    // A resource worth `value` credits was mined
+    Mined { value: u32 },
+}
+
+// One beep, plus one more for every tier threshold the value reaches
+pub fn beep_count(cue: Cue, value_tiers: &[u32]) -> u32 {
+    match cue {
+        Cue::Mined { value } => 1 + value_tiers.iter().filter(|&&t| value >= t).count() as u32,
+    }
+}
+
+pub struct Sound {
+    enabled: bool,
+    value_tiers: Vec<u32>,
+}
+
+impl Sound {
+    pub fn new(enabled: bool, value_tiers: Vec<u32>) -> Self {
+        Sound { enabled, value_tiers }
+    }
+
+    // Plays in the background so the game loop never waits on it
+    pub fn play(&self, cue: Cue) {
+        if !self.enabled {
+            return;
+        }
+        let beeps = beep_count(cue, &self.value_tiers);
+        tokio::spawn(async move {
+            for i in 0..beeps {
+                if i > 0 {
+                    tokio::time::sleep(BEEP_GAP).await;
+                }
+                let mut out = stdout();
+                let _ = out.write_all(b"\x07");
+                let _ = out.flush();
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn more_valuable_mines_beep_more() {
+        let tiers = [10, 30];
+        assert_eq!(beep_count(Cue::Mined { value: 5 }, &tiers), 1);
+        assert_eq!(beep_count(Cue::Mined { value: 10 }, &tiers), 2);
+        assert_eq!(beep_count(Cue::Mined { value: 50 }, &tiers), 3);
+        assert_eq!(beep_count(Cue::Mined { value: 50 }, &[]), 1);
+    }
+
+    #[test]
+    fn default_tiers_beep_once_twice_and_three_times() {
+        let settings = crate::config::Settings::default();
+        let beeps = |kind| beep_count(Cue::Mined { value: settings.resource_values[&kind] }, &settings.sound_value_tiers);
+        assert_eq!(beeps(crate::Resource::Iron), 1);
+        assert_eq!(beeps(crate::Resource::Crystal), 2);
+        assert_eq!(beeps(crate::Resource::Gold), 3);
+    }
+}
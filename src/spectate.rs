@@ -0,0 +1,110 @@
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use serde::Serialize;
+use crate::{Asteroid, GameMode, GameState, ResourceNode, Ship};
+
+// --- Spectating ---
+// Streams the game to local TCP clients as one JSON object per line, for
+// spectators and streaming overlays. Never waits on a client: anyone who
+// can't keep up is dropped.
+
+#[derive(Serialize)]
+struct SpectatorFrame<'a> {
+    tick: u32,
+    mode: GameMode,
+    score: u32,
+    ship: &'a Ship,
+    ally: Option<&'a Ship>,
+    asteroids: &'a [Asteroid],
+    resources: &'a [ResourceNode],
+}
+
+pub struct Broadcaster {
+    listener: TcpListener,
+    clients: Vec<TcpStream>,
+}
+
+impl Broadcaster {
+    pub fn bind(port: u16) -> Result<Self, String> {
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .map_err(|e| format!("could not listen on port {}: {}", port, e))?;
+        listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+        Ok(Broadcaster { listener, clients: Vec::new() })
+    }
+
+    // Stops at WouldBlock once there's nobody left waiting
+    fn accept_clients(&mut self) {
+        while let Ok((stream, _)) = self.listener.accept() {
+            if stream.set_nonblocking(true).is_ok() {
+                self.clients.push(stream);
+            }
+        }
+    }
+
+    pub fn broadcast(&mut self, state: &GameState) {
+        self.accept_clients();
+        if self.clients.is_empty() {
+            return;
+        }
+        let frame = SpectatorFrame {
+            tick: state.tick,
+            mode: state.mode,
+            score: state.score,
+            ship: &state.ship,
+            ally: state.ally.as_ref(),
+            asteroids: &state.asteroids,
+            resources: &state.resources,
+        };
+        let Ok(mut line) = serde_json::to_string(&frame) else { return };
+        line.push('\n');
+        // A client whose buffer is full would get a torn frame, so it's cut off
+        self.clients.retain_mut(|client| client.write_all(line.as_bytes()).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::time::Duration;
+    use crate::arena::Arena;
+    use crate::config::Settings;
+    use crate::InputEvent;
+
+    fn game() -> GameState {
+        GameState::new(Settings { skip_intro: true, seed: Some(7), ..Settings::default() }, Arena::default())
+    }
+
+    #[test]
+    fn connected_client_gets_a_line_per_frame() {
+        let mut broadcaster = Broadcaster::bind(0).unwrap();
+        let addr = broadcaster.listener.local_addr().unwrap();
+        let client = TcpStream::connect(addr).unwrap();
+        let mut state = game();
+        state.asteroids.clear();
+        broadcaster.broadcast(&state);
+        state.step(&InputEvent::None, Duration::from_millis(100));
+        broadcaster.broadcast(&state);
+
+        let mut lines = BufReader::new(client).lines();
+        for tick in [0, 1] {
+            let frame: serde_json::Value = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+            assert_eq!(frame["tick"], tick);
+            assert_eq!(frame["mode"], "Playing");
+            assert!(frame["ship"]["fuel"].is_number());
+        }
+    }
+
+    #[test]
+    fn game_runs_on_with_nobody_watching() {
+        let mut broadcaster = Broadcaster::bind(0).unwrap();
+        let mut state = game();
+        state.asteroids.clear();
+        for _ in 0..5 {
+            state.step(&InputEvent::None, Duration::from_millis(100));
+            broadcaster.broadcast(&state);
+        }
+        assert!(broadcaster.clients.is_empty());
+        assert_eq!(state.tick, 5);
+    }
+}
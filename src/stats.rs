@@ -0,0 +1,156 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use crate::DeathCause;
+use crate::config::{Difficulty, LevelType};
+
+pub const HISTORY_FILE: &str = "history.jsonl";
+// Past this size the history is moved aside to `history.jsonl.1` and started afresh
+const HISTORY_MAX_BYTES: u64 = 1024 * 1024;
+
+// --- Run Statistics ---
+// Counters accumulated over a single run
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunStats {
+    // Movement inputs, whether or not the ship could actually move
+    pub moves: u32,
+    // Resource nodes mined
+    pub mines: u32,
+    // Cells actually travelled
+    pub distance: u32,
+    // Resource spawns skipped for want of a free cell, for tuning spawn rates
+    pub spawn_failures: u32,
+}
+
+pub fn per_minute(count: u32, elapsed: Duration) -> f32 {
+    let minutes = elapsed.as_secs_f32() / 60.0;
+    if minutes > 0.0 { count as f32 / minutes } else { 0.0 }
+}
+
+impl RunStats {
+    pub fn moves_per_minute(&self, elapsed: Duration) -> f32 {
+        per_minute(self.moves, elapsed)
+    }
+
+    pub fn mines_per_minute(&self, elapsed: Duration) -> f32 {
+        per_minute(self.mines, elapsed)
+    }
+}
+
+// --- Run Summary ---
+// Written as JSON at the end of a run for players tracking their performance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunSummary {
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub level: LevelType,
+    pub score: u32,
+    pub stats: RunStats,
+    // None if the player quit
+    pub death_cause: Option<DeathCause>,
+    pub duration_secs: f64,
+    // The run's win condition was met
+    pub victory: bool,
+}
+
+impl RunSummary {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("run summary is always serializable")
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        fs::write(path, self.to_json()).map_err(|e| format!("could not write {}: {}", path.display(), e))
+    }
+
+    // Adds the run to a JSON-lines play history, rotating the file once it
+    // grows past `max_bytes` so it can't grow without bound
+    pub fn append_to_history(&self, path: &Path, max_bytes: u64) -> Result<(), String> {
+        let err = |e: std::io::Error| format!("could not write {}: {}", path.display(), e);
+        if fs::metadata(path).is_ok_and(|m| m.len() >= max_bytes) {
+            let mut rotated = path.as_os_str().to_owned();
+            rotated.push(".1");
+            fs::rename(path, rotated).map_err(err)?;
+        }
+        let line = serde_json::to_string(self).expect("run summary is always serializable");
+        let mut file = OpenOptions::new().create(true).append(true).open(path).map_err(err)?;
+        writeln!(file, "{}", line).map_err(err)
+    }
+}
+
+pub fn record_history(summary: &RunSummary) -> Result<(), String> {
+    summary.append_to_history(Path::new(HISTORY_FILE), HISTORY_MAX_BYTES)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rates_are_per_minute_of_play() {
+        let stats = RunStats { moves: 30, mines: 3, ..RunStats::default() };
+        assert_eq!(stats.moves_per_minute(Duration::from_secs(30)), 60.0);
+        assert_eq!(stats.mines_per_minute(Duration::from_secs(120)), 1.5);
+        assert_eq!(stats.moves_per_minute(Duration::ZERO), 0.0);
+    }
+
+    fn summary() -> RunSummary {
+        RunSummary {
+            seed: 7,
+            difficulty: Difficulty::Hard,
+            level: LevelType::Field,
+            score: 120,
+            stats: RunStats { moves: 4, mines: 2, distance: 5, spawn_failures: 1 },
+            death_cause: Some(DeathCause::OutOfFuel),
+            duration_secs: 12.5,
+            victory: false,
+        }
+    }
+
+    fn temp_file(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("rusty-space-miner-{}-{}", std::process::id(), name));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn summary_is_written_as_json() {
+        let path = temp_file("summary.json");
+        summary().write(&path).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(value["seed"], 7);
+        assert_eq!(value["difficulty"], "Hard");
+        assert_eq!(value["score"], 120);
+        assert_eq!(value["stats"]["spawn_failures"], 1);
+        assert_eq!(value["death_cause"], "OutOfFuel");
+        assert_eq!(value["duration_secs"], 12.5);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn each_run_appends_one_line_to_the_history() {
+        let path = temp_file("history.jsonl");
+        summary().append_to_history(&path, 1 << 20).unwrap();
+        summary().append_to_history(&path, 1 << 20).unwrap();
+        let history = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = history.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let run: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(run["score"], 120);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn history_rotates_past_the_size_cap() {
+        let path = temp_file("rotating.jsonl");
+        let rotated = temp_file("rotating.jsonl.1");
+        summary().append_to_history(&path, 1).unwrap();
+        // The file is now over the cap, so the next run starts a fresh one
+        summary().append_to_history(&path, 1).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 1);
+        assert_eq!(fs::read_to_string(&rotated).unwrap().lines().count(), 1);
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&rotated).unwrap();
+    }
+}
@@ -0,0 +1,72 @@
+use std::fs;
+use std::path::Path;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+// --- JSON Files ---
+// Reads `path` with `parse`; a file that doesn't exist yet is the default
+pub fn load_with<T: Default>(path: &Path, parse: impl FnOnce(&str) -> Result<T, String>) -> Result<T, String> {
+    match fs::read_to_string(path) {
+        Ok(text) => parse(&text).map_err(|e| format!("invalid {}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(format!("could not read {}: {}", path.display(), e)),
+    }
+}
+
+pub fn load_or_default<T: DeserializeOwned + Default>(path: &Path) -> Result<T, String> {
+    load_with(path, |text| serde_json::from_str(text).map_err(|e| e.to_string()))
+}
+
+// Pretty-printed, for files players might read or edit
+pub fn save<T: Serialize>(value: &T, path: &Path) -> Result<(), String> {
+    write(path, serde_json::to_string_pretty(value))
+}
+
+// On one line, for bulky files nobody reads by hand
+pub fn save_compact<T: Serialize>(value: &T, path: &Path) -> Result<(), String> {
+    write(path, serde_json::to_string(value))
+}
+
+fn write(path: &Path, text: serde_json::Result<String>) -> Result<(), String> {
+    let text = text.map_err(|e| e.to_string())?;
+    fs::write(path, text).map_err(|e| format!("could not write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn temp_file(name: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("rusty-space-miner-{}-{}", std::process::id(), name));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn missing_file_loads_the_default() {
+        let path = temp_file("storage-missing.json");
+        let loaded: HashMap<String, u32> = load_or_default(&path).unwrap();
+        assert!(loaded.is_empty());
+    }
+
+    #[test]
+    fn saved_value_loads_back() {
+        let path = temp_file("storage-round-trip.json");
+        let value = HashMap::from([("gold".to_string(), 3)]);
+        save(&value, &path).unwrap();
+        assert_eq!(load_or_default::<HashMap<String, u32>>(&path).unwrap(), value);
+        save_compact(&value, &path).unwrap();
+        assert_eq!(load_or_default::<HashMap<String, u32>>(&path).unwrap(), value);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn corrupt_file_is_an_error() {
+        let path = temp_file("storage-corrupt.json");
+        fs::write(&path, "{ not json").unwrap();
+        let loaded: Result<HashMap<String, u32>, _> = load_or_default(&path);
+        assert!(loaded.unwrap_err().starts_with("invalid"));
+        fs::remove_file(&path).unwrap();
+    }
+}
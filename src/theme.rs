@@ -0,0 +1,135 @@
+use crossterm::style::Color;
+use serde::{Serialize, Deserialize};
+use unicode_width::UnicodeWidthChar;
+use crate::{Asteroid, SHIP_WIDTH};
+
+// --- Theme ---
+// Colors players can restyle from settings.json, e.g. "dark_yellow" or "rgb_(255,128,0)"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Theme {
+    // Stationary asteroids; unset uses the terminal's default color
+    pub asteroid: Option<Color>,
+    // Moving asteroids by speed in cells per tick, from 1 upwards; anything
+    // faster than the list covers uses the last color
+    pub asteroid_speeds: Vec<Color>,
+    // Ship glyphs, each exactly as wide as the ship's hitbox
+    pub ship_glyph: String,
+    pub ally_glyph: String,
+    // Playfield border and HUD text; unset uses the terminal's default color
+    pub border: Option<Color>,
+    pub hud_label: Option<Color>,
+    pub hud_value: Option<Color>,
+    // Minimap resource dots by credit value: each node takes the color of the
+    // highest threshold its value reaches, e.g. [[0, "grey"], [50, "yellow"]]
+    pub minimap_colors: Vec<(u32, Color)>,
+    // Draw rich nodes as bigger dots on the minimap
+    pub minimap_richness: bool,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            asteroid: None,
+            asteroid_speeds: vec![Color::Yellow, Color::DarkYellow, Color::Red],
+            ship_glyph: ">A<".to_string(),
+            ally_glyph: "<B>".to_string(),
+            border: None,
+            hud_label: None,
+            hud_value: None,
+            minimap_colors: vec![(0, Color::Grey), (20, Color::Cyan), (50, Color::Yellow)],
+            minimap_richness: true,
+        }
+    }
+}
+
+impl Theme {
+    // A glyph wider or narrower than the hitbox would show the ship somewhere
+    // it can't be hit, or hide where it can. Each character fills one cell, so
+    // wide (e.g. CJK), zero-width and control characters are turned away too.
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, glyph) in [("ship_glyph", &self.ship_glyph), ("ally_glyph", &self.ally_glyph)] {
+            if let Some(ch) = glyph.chars().find(|c| c.width() != Some(1)) {
+                return Err(format!("theme.{} {:?} has {:?}, which isn't one cell wide", name, glyph, ch));
+            }
+            let width = glyph.chars().count();
+            if width != SHIP_WIDTH as usize {
+                return Err(format!("theme.{} {:?} is {} cells wide, the ship is {}", name, glyph, width, SHIP_WIDTH));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn minimap_color(&self, value: u32) -> Color {
+        self.minimap_colors
+            .iter()
+            .filter(|(threshold, _)| value >= *threshold)
+            .max_by_key(|(threshold, _)| *threshold)
+            .map_or(Color::Grey, |(_, color)| *color)
+    }
+
+    // Faster asteroids are tinted hotter so threat speed reads at a glance
+    pub fn asteroid_color(&self, a: &Asteroid) -> Option<Color> {
+        let speed = a.vx.unsigned_abs().max(a.vy.unsigned_abs()) as usize;
+        if speed == 0 || self.asteroid_speeds.is_empty() {
+            return self.asteroid;
+        }
+        Some(self.asteroid_speeds[(speed - 1).min(self.asteroid_speeds.len() - 1)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_glyph(glyph: &str) -> Theme {
+        Theme { ship_glyph: glyph.to_string(), ..Theme::default() }
+    }
+
+    #[test]
+    fn single_width_glyph_is_accepted() {
+        assert!(with_glyph(">A<").validate().is_ok());
+        assert!(with_glyph("►◊◄").validate().is_ok());
+    }
+
+    #[test]
+    fn wrong_length_glyph_is_rejected() {
+        assert!(with_glyph("><").validate().is_err());
+        assert!(with_glyph(">AA<").validate().is_err());
+    }
+
+    #[test]
+    fn wide_or_zero_width_glyph_is_rejected() {
+        // Three characters but four cells on screen
+        assert!(with_glyph(">船<").validate().is_err());
+        // Three characters but a combining accent takes no cell of its own
+        assert!(with_glyph(">A\u{301}").validate().is_err());
+        assert!(with_glyph(">\t<").validate().is_err());
+    }
+
+    #[test]
+    fn faster_asteroids_are_tinted_hotter() {
+        let theme = Theme::default();
+        let rock = |vx, vy| Asteroid { x: 0, y: 0, vx, vy };
+        assert_eq!(theme.asteroid_color(&rock(0, 0)), None);
+        assert_eq!(theme.asteroid_color(&rock(-1, 0)), Some(Color::Yellow));
+        assert_eq!(theme.asteroid_color(&rock(0, 2)), Some(Color::DarkYellow));
+        // Anything faster than the list covers takes the last color
+        assert_eq!(theme.asteroid_color(&rock(5, -1)), Some(Color::Red));
+    }
+
+    #[test]
+    fn no_speed_colors_leaves_moving_asteroids_plain() {
+        let theme = Theme { asteroid: Some(Color::Grey), asteroid_speeds: Vec::new(), ..Theme::default() };
+        assert_eq!(theme.asteroid_color(&Asteroid { x: 0, y: 0, vx: 3, vy: 0 }), Some(Color::Grey));
+    }
+
+    #[test]
+    fn minimap_color_takes_the_highest_threshold_reached() {
+        let theme = Theme::default();
+        assert_eq!(theme.minimap_color(0), Color::Grey);
+        assert_eq!(theme.minimap_color(20), Color::Cyan);
+        assert_eq!(theme.minimap_color(49), Color::Cyan);
+        assert_eq!(theme.minimap_color(500), Color::Yellow);
+    }
+}
@@ -0,0 +1,153 @@
+use std::path::Path;
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use crate::Rect;
+use crate::geometry::rect_overlap;
+use crate::storage;
+
+pub const BEST_SPLITS_FILE: &str = "splits.json";
+
+// Gates across the field and how tall each one is
+const GATE_COUNT: u16 = 4;
+const GATE_HEIGHT: u16 = 3;
+
+// --- Time Trial ---
+// Checkpoint gates the ship has to fly through in order, against the clock
+#[derive(Debug, Clone)]
+pub struct TimeTrial {
+    pub gates: Vec<Rect>,
+    // Index of the gate to pass next
+    pub next: usize,
+    // Time into the run each gate was passed
+    pub splits: Vec<Duration>,
+}
+
+// Gates spread evenly left to right, alternating near the top and bottom
+pub fn checkpoint_gates(bounds: &Rect) -> Vec<Rect> {
+    (0..GATE_COUNT)
+        .map(|i| {
+            let x = bounds.x + bounds.w * (i + 1) / (GATE_COUNT + 1);
+            let y = if i % 2 == 0 { bounds.y + 1 } else { (bounds.y + bounds.h).saturating_sub(GATE_HEIGHT + 1) };
+            Rect { x, y, w: 1, h: GATE_HEIGHT }
+        })
+        .collect()
+}
+
+impl TimeTrial {
+    pub fn new(gates: Vec<Rect>) -> Self {
+        TimeTrial { gates, next: 0, splits: Vec::new() }
+    }
+
+    // Records a split if `hitbox` is in the next gate; any other gate doesn't count
+    pub fn update(&mut self, hitbox: &Rect, elapsed: Duration) -> bool {
+        match self.gates.get(self.next) {
+            Some(gate) if rect_overlap(hitbox, gate) => {
+                self.next += 1;
+                self.splits.push(elapsed);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn finished(&self) -> bool {
+        self.next >= self.gates.len()
+    }
+}
+
+// --- Best Splits ---
+// The fastest time to each gate, over every finished trial
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BestSplits {
+    // The course the splits were set on; older files without it never match
+    #[serde(default)]
+    pub gates: Vec<Rect>,
+    pub splits: Vec<f32>,
+}
+
+impl BestSplits {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        storage::load_or_default(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        storage::save(self, path)
+    }
+
+    // Whether these bests were set on the course with `gates`
+    pub fn same_course(&self, gates: &[Rect]) -> bool {
+        self.gates == gates && self.splits.len() == gates.len()
+    }
+
+    // Keeps the faster of the old and new time at each gate. Splits from a
+    // different course layout are replaced outright.
+    pub fn merge(&mut self, gates: &[Rect], splits: &[Duration]) {
+        let secs = splits.iter().map(|s| s.as_secs_f32());
+        if !self.same_course(gates) || splits.len() != gates.len() {
+            self.gates = gates.to_vec();
+            self.splits = secs.collect();
+            return;
+        }
+        for (best, new) in self.splits.iter_mut().zip(secs) {
+            *best = best.min(new);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gates() -> Vec<Rect> {
+        checkpoint_gates(&Rect { x: 0, y: 0, w: 30, h: 15 })
+    }
+
+    fn cell(x: u16, y: u16) -> Rect {
+        Rect { x, y, w: 1, h: 1 }
+    }
+
+    fn secs(splits: &[u64]) -> Vec<Duration> {
+        splits.iter().map(|&s| Duration::from_secs(s)).collect()
+    }
+
+    #[test]
+    fn gates_pass_in_order() {
+        let mut trial = TimeTrial::new(gates());
+        for (i, gate) in gates().iter().enumerate() {
+            assert!(trial.update(&cell(gate.x, gate.y), Duration::from_secs(i as u64 + 1)));
+        }
+        assert!(trial.finished());
+        assert_eq!(trial.splits, secs(&[1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn out_of_order_gate_is_ignored() {
+        let gates = gates();
+        let mut trial = TimeTrial::new(gates.clone());
+        assert!(!trial.update(&cell(gates[1].x, gates[1].y), Duration::from_secs(1)));
+        assert_eq!(trial.next, 0);
+        assert!(trial.splits.is_empty());
+        assert!(trial.update(&cell(gates[0].x, gates[0].y), Duration::from_secs(2)));
+        assert_eq!(trial.next, 1);
+    }
+
+    #[test]
+    fn merge_keeps_the_faster_split_at_each_gate() {
+        let mut best = BestSplits::default();
+        best.merge(&gates(), &secs(&[2, 4, 6, 8]));
+        best.merge(&gates(), &secs(&[3, 4, 5, 9]));
+        assert_eq!(best.splits, vec![2.0, 4.0, 5.0, 8.0]);
+    }
+
+    #[test]
+    fn merge_replaces_bests_from_another_course() {
+        let mut best = BestSplits::default();
+        best.merge(&gates(), &secs(&[2, 4, 6, 8]));
+        // Same number of gates, laid out on a bigger world
+        let other = checkpoint_gates(&Rect { x: 0, y: 0, w: 60, h: 30 });
+        assert!(!best.same_course(&other));
+        best.merge(&other, &secs(&[5, 10, 15, 20]));
+        assert_eq!(best.splits, vec![5.0, 10.0, 15.0, 20.0]);
+        assert!(best.same_course(&other));
+    }
+}
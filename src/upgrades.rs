@@ -0,0 +1,76 @@
+use serde::{Serialize, Deserialize};
+
+// --- Upgrades ---
+// An upgrade is a named bundle of effects, so new ones can be described in
+// settings.json, e.g. {"name": "Hauler", "effects": [{"FuelCapacity": 50}, {"MoveSpeed": 1}]}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Effect {
+    // Extra fuel the tank holds
+    FuelCapacity(f32),
+    // Extra cells moved per step
+    MoveSpeed(u16),
+    // Halves mining time
+    Laser,
+    // Absorbs one asteroid strike, destroying the asteroid
+    Shields,
+    // Draws nearby resource nodes in towards the ship
+    Magnet,
+    // A companion that collects resources on its own
+    Drone,
+    // Picks out contaminated resource nodes
+    Scanner,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpgradeDef {
+    pub name: String,
+    pub effects: Vec<Effect>,
+}
+
+impl UpgradeDef {
+    fn new(name: &str, effects: Vec<Effect>) -> Self {
+        UpgradeDef { name: name.to_string(), effects }
+    }
+}
+
+// Upgrades that exist without any configuration
+pub fn builtin_upgrades() -> Vec<UpgradeDef> {
+    vec![
+        UpgradeDef::new("Laser", vec![Effect::Laser]),
+        UpgradeDef::new("Shields", vec![Effect::Shields]),
+        UpgradeDef::new("Thrusters", vec![Effect::MoveSpeed(1)]),
+        UpgradeDef::new("Magnet", vec![Effect::Magnet]),
+        UpgradeDef::new("Drone", vec![Effect::Drone]),
+        UpgradeDef::new("Scanner", vec![Effect::Scanner]),
+    ]
+}
+
+// Looks `name` up among the configured upgrades, which may replace a built-in
+pub fn find_upgrade(name: &str, configured: &[UpgradeDef]) -> Option<UpgradeDef> {
+    configured
+        .iter()
+        .find(|u| u.name == name)
+        .cloned()
+        .or_else(|| builtin_upgrades().into_iter().find(|u| u.name == name))
+}
+
+pub fn effects(upgrades: &[UpgradeDef]) -> impl Iterator<Item = &Effect> {
+    upgrades.iter().flat_map(|u| &u.effects)
+}
+
+pub fn has_effect(upgrades: &[UpgradeDef], effect: &Effect) -> bool {
+    effects(upgrades).any(|e| e == effect)
+}
+
+pub fn fuel_capacity_bonus(upgrades: &[UpgradeDef]) -> f32 {
+    effects(upgrades).map(|e| if let Effect::FuelCapacity(extra) = e { *extra } else { 0.0 }).sum()
+}
+
+pub fn move_speed_bonus(upgrades: &[UpgradeDef]) -> u16 {
+    effects(upgrades).map(|e| if let Effect::MoveSpeed(extra) = e { *extra } else { 0 }).sum()
+}
+
+// Asteroid strikes a ship fitted with these upgrades can shrug off
+pub fn shield_charges(upgrades: &[UpgradeDef]) -> u32 {
+    effects(upgrades).filter(|e| **e == Effect::Shields).count() as u32
+}